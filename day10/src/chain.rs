@@ -0,0 +1,413 @@
+use std::collections::BTreeMap;
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+use rand::Rng;
+
+/// How far above the highest adapter the device's built-in adapter is rated, which is also the
+/// largest gap it can take as input regardless of the tolerance of the others.
+const DEVICE_OFFSET: i64 = 3;
+
+/// The joltages of a full chain, from the outlet through every adapter to the device.
+#[derive(Clone, Debug)]
+pub struct AdapterChain {
+    joltages: Vec<i64>,
+    tolerance: u16,
+}
+
+impl AdapterChain {
+    /// Sorts the adapters into a chain where each can take an input 1 to `tolerance` jolts lower,
+    /// checking each one can connect to the next.
+    pub fn with_tolerance(mut adapters: Vec<i64>, tolerance: u16) -> Result<Self, String> {
+        if tolerance == 0 {
+            return Err(String::from("Tolerance must be at least 1"));
+        }
+
+        adapters.sort_unstable();
+
+        // Without any adapters the device connects straight to the outlet
+        let highest = adapters.last().copied().unwrap_or_default();
+        let device = highest
+            .checked_add(DEVICE_OFFSET)
+            .ok_or(format!("Device rating above {} is too large", highest))?;
+
+        let mut joltages = vec![0];
+        joltages.extend(adapters);
+        joltages.push(device);
+
+        let chain = Self {
+            joltages,
+            tolerance,
+        };
+
+        for (i, window) in chain.joltages.windows(2).enumerate() {
+            let gap = window[1] - window[0];
+
+            if !(1..=chain.reach(i + 1)).contains(&gap) {
+                return Err(format!(
+                    "Gap of {} jolts between {} and {} cannot be bridged",
+                    gap, window[0], window[1]
+                ));
+            }
+        }
+
+        Ok(chain)
+    }
+
+    /// Counts how many times each difference in joltage appears in the chain.
+    pub fn gaps(&self) -> BTreeMap<i64, usize> {
+        let mut gaps = BTreeMap::new();
+
+        for window in self.joltages.windows(2) {
+            *gaps.entry(window[1] - window[0]).or_default() += 1;
+        }
+
+        gaps
+    }
+
+    /// Gets the largest gap the joltage at `index` can take as input.
+    fn reach(&self, index: usize) -> i64 {
+        if index == self.joltages.len() - 1 {
+            DEVICE_OFFSET
+        } else {
+            i64::from(self.tolerance)
+        }
+    }
+
+    /// Gets the indices of the joltages that can connect directly to the one at `index`, which
+    /// are at most `reach` positions back as joltages are distinct.
+    fn sources(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let earliest = index.saturating_sub(self.reach(index) as usize);
+
+        (earliest..index)
+            .filter(move |j| self.joltages[index] - self.joltages[*j] <= self.reach(index))
+    }
+
+    /// Gets the indices of the joltages that the one at `index` can connect directly to.
+    fn targets(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let latest = index
+            .saturating_add(usize::from(self.tolerance))
+            .min(self.joltages.len() - 1);
+
+        (index + 1..=latest)
+            .filter(move |j| self.joltages[*j] - self.joltages[index] <= self.reach(*j))
+    }
+
+    /// Counts the ways of connecting the outlet to each joltage in the chain.
+    pub fn arrangement_table(&self) -> Vec<BigUint> {
+        let mut counts = vec![BigUint::zero(); self.joltages.len()];
+        counts[0] = BigUint::one();
+
+        for i in 1..self.joltages.len() {
+            counts[i] = self.sources(i).map(|j| &counts[j]).sum();
+        }
+
+        counts
+    }
+
+    /// Counts the ways of connecting each joltage in the chain to the device.
+    fn completion_table(&self) -> Vec<BigUint> {
+        let end = self.joltages.len() - 1;
+        let mut counts = vec![BigUint::zero(); self.joltages.len()];
+        counts[end] = BigUint::one();
+
+        for i in (0..end).rev() {
+            counts[i] = self.targets(i).map(|j| &counts[j]).sum();
+        }
+
+        counts
+    }
+
+    /// Counts the ways of connecting the outlet to the device with some of the adapters.
+    pub fn count_arrangements(&self) -> BigUint {
+        self.arrangement_table().pop().unwrap()
+    }
+
+    /// Finds the fewest adapters that still connect the outlet to the device.
+    pub fn minimal_subset(&self) -> Vec<i64> {
+        let end = self.joltages.len() - 1;
+
+        let mut subset = Vec::new();
+        let mut current = 0;
+
+        // Jumping as far as possible each time can never need more steps than any other choice
+        while current != end {
+            current = self.targets(current).last().unwrap();
+
+            if current != end {
+                subset.push(self.joltages[current]);
+            }
+        }
+
+        subset
+    }
+
+    /// Finds the adapters that appear in every valid arrangement.
+    pub fn mandatory(&self) -> Vec<i64> {
+        let arrivals = self.arrangement_table();
+        let completions = self.completion_table();
+        let total = arrivals.last().unwrap();
+
+        // An adapter is in every arrangement if every route to the device goes through it
+        (1..self.joltages.len() - 1)
+            .filter(|i| &(&arrivals[*i] * &completions[*i]) == total)
+            .map(|i| self.joltages[i])
+            .collect()
+    }
+
+    /// Finds the adapters that can be taken out while still using all of the others.
+    pub fn removable(&self) -> Vec<i64> {
+        self.joltages
+            .windows(3)
+            .enumerate()
+            .filter(|(i, window)| window[2] - window[0] <= self.reach(i + 2))
+            .map(|(_, window)| window[1])
+            .collect()
+    }
+
+    /// Lists every valid arrangement of adapters, using as many adapters as possible first.
+    pub fn arrangements(&self) -> Arrangements<'_> {
+        Arrangements {
+            chain: self,
+            path: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// Picks an arrangement of adapters uniformly at random.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Vec<i64> {
+        let completions = self.completion_table();
+        let end = self.joltages.len() - 1;
+
+        let mut arrangement = Vec::new();
+        let mut current = 0;
+
+        // Weight each step by the number of ways of finishing the chain from there
+        while current != end {
+            let mut choice = rng.gen_biguint_below(&completions[current]);
+
+            for target in self.targets(current) {
+                if choice < completions[target] {
+                    current = target;
+                    break;
+                }
+
+                choice -= &completions[target];
+            }
+
+            if current != end {
+                arrangement.push(self.joltages[current]);
+            }
+        }
+
+        arrangement
+    }
+}
+
+/// An iterator over the joltages of the adapters used in each valid arrangement.
+pub struct Arrangements<'a> {
+    chain: &'a AdapterChain,
+    path: Vec<usize>,
+    finished: bool,
+}
+
+impl Arrangements<'_> {
+    /// Extends the path to the device, always taking the next adapter in the chain.
+    fn complete(&mut self) -> Vec<i64> {
+        let end = self.chain.joltages.len() - 1;
+        let last = *self.path.last().unwrap();
+
+        self.path.extend(last + 1..=end);
+
+        self.path[1..self.path.len() - 1]
+            .iter()
+            .map(|i| self.chain.joltages[*i])
+            .collect()
+    }
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<i64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        if self.path.is_empty() {
+            self.path.push(0);
+            return Some(self.complete());
+        }
+
+        // Backtrack to the most recent step that can skip ahead further instead
+        loop {
+            let skipped = self.path.pop().unwrap();
+
+            let previous = match self.path.last() {
+                Some(previous) => *previous,
+                None => {
+                    self.finished = true;
+                    return None;
+                }
+            };
+
+            if self.chain.targets(previous).any(|t| t == skipped + 1) {
+                self.path.push(skipped + 1);
+                return Some(self.complete());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL_EXAMPLE: &[i64] = &[16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
+
+    const LARGE_EXAMPLE: &[i64] = &[
+        28, 33, 18, 42, 31, 14, 46, 20, 48, 47, 24, 23, 49, 45, 19, 38, 39, 11, 1, 32, 25, 35, 8,
+        17, 7, 9, 4, 2, 34, 10, 3,
+    ];
+
+    fn chain(adapters: &[i64]) -> AdapterChain {
+        AdapterChain::with_tolerance(adapters.to_vec(), 3).unwrap()
+    }
+
+    #[test]
+    fn gaps_are_counted_across_the_whole_chain() {
+        let expected: BTreeMap<_, _> = vec![(1, 7), (3, 5)].into_iter().collect();
+        assert_eq!(chain(SMALL_EXAMPLE).gaps(), expected);
+
+        let expected: BTreeMap<_, _> = vec![(1, 22), (3, 10)].into_iter().collect();
+        assert_eq!(chain(LARGE_EXAMPLE).gaps(), expected);
+    }
+
+    #[test]
+    fn gaps_larger_than_the_tolerance_are_rejected() {
+        assert_eq!(
+            AdapterChain::with_tolerance(vec![1, 2, 6], 3).unwrap_err(),
+            "Gap of 4 jolts between 2 and 6 cannot be bridged"
+        );
+        assert_eq!(
+            AdapterChain::with_tolerance(vec![4], 3).unwrap_err(),
+            "Gap of 4 jolts between 0 and 4 cannot be bridged"
+        );
+    }
+
+    #[test]
+    fn devices_can_connect_straight_to_the_outlet() {
+        let chain = AdapterChain::with_tolerance(Vec::new(), 3).unwrap();
+
+        assert_eq!(chain.joltages, vec![0, 3]);
+        assert_eq!(chain.gaps().get(&3), Some(&1));
+        assert_eq!(chain.count_arrangements(), BigUint::one());
+        assert!(chain.minimal_subset().is_empty());
+    }
+
+    #[test]
+    fn arrangements_are_counted() {
+        assert_eq!(
+            chain(SMALL_EXAMPLE).count_arrangements(),
+            BigUint::from(8u32)
+        );
+        assert_eq!(
+            chain(LARGE_EXAMPLE).count_arrangements(),
+            BigUint::from(19208u32)
+        );
+    }
+
+    #[test]
+    fn the_device_is_always_rated_above_the_highest_adapter() {
+        let chain = AdapterChain::with_tolerance(SMALL_EXAMPLE.to_vec(), 5).unwrap();
+        assert_eq!(chain.joltages.last(), Some(&22));
+        assert_eq!(chain.gaps().get(&3), Some(&5));
+
+        // The device can take a gap of 3 even when the adapters can't
+        let chain = AdapterChain::with_tolerance(vec![1, 2, 3], 1).unwrap();
+        assert_eq!(chain.joltages, vec![0, 1, 2, 3, 6]);
+        assert_eq!(chain.count_arrangements(), BigUint::one());
+
+        // A wide tolerance between adapters doesn't let the device take more than 3
+        let chain = AdapterChain::with_tolerance(vec![1, 2], 10).unwrap();
+        assert_eq!(chain.count_arrangements(), BigUint::from(2u32));
+        assert_eq!(chain.removable(), vec![1]);
+    }
+
+    #[test]
+    fn tolerances_and_ratings_are_bounded() {
+        assert!(AdapterChain::with_tolerance(vec![1], 0).is_err());
+        assert!(AdapterChain::with_tolerance(vec![1], u16::MAX).is_ok());
+        assert_eq!(
+            AdapterChain::with_tolerance(vec![i64::MAX], u16::MAX).unwrap_err(),
+            format!("Device rating above {} is too large", i64::MAX)
+        );
+    }
+
+    #[test]
+    fn every_arrangement_is_listed_once() {
+        for adapters in &[SMALL_EXAMPLE, LARGE_EXAMPLE] {
+            let chain = chain(adapters);
+            let arrangements: Vec<_> = chain.arrangements().collect();
+            let distinct: std::collections::HashSet<_> = arrangements.iter().collect();
+
+            assert_eq!(
+                BigUint::from(arrangements.len()),
+                chain.count_arrangements()
+            );
+            assert_eq!(distinct.len(), arrangements.len());
+        }
+
+        let mut adapters = SMALL_EXAMPLE.to_vec();
+        adapters.sort_unstable();
+
+        let chain = chain(SMALL_EXAMPLE);
+        let mut arrangements = chain.arrangements();
+        assert_eq!(arrangements.next(), Some(adapters));
+        assert_eq!(arrangements.last(), Some(vec![1, 4, 7, 10, 12, 15, 16, 19]));
+    }
+
+    #[test]
+    fn samples_are_valid_arrangements() {
+        let chain = chain(SMALL_EXAMPLE);
+        let arrangements: Vec<_> = chain.arrangements().collect();
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..100 {
+            assert!(arrangements.contains(&chain.sample(&mut rng)));
+        }
+    }
+
+    #[test]
+    fn minimal_subsets_take_the_largest_step_each_time() {
+        assert_eq!(
+            chain(SMALL_EXAMPLE).minimal_subset(),
+            vec![1, 4, 7, 10, 12, 15, 16, 19]
+        );
+        assert_eq!(
+            chain(LARGE_EXAMPLE).minimal_subset(),
+            vec![3, 4, 7, 10, 11, 14, 17, 20, 23, 25, 28, 31, 34, 35, 38, 39, 42, 45, 48, 49]
+        );
+    }
+
+    #[test]
+    fn adapters_next_to_gaps_of_three_are_mandatory() {
+        assert_eq!(
+            chain(SMALL_EXAMPLE).mandatory(),
+            vec![1, 4, 7, 10, 12, 15, 16, 19]
+        );
+        assert_eq!(
+            chain(LARGE_EXAMPLE).mandatory(),
+            vec![4, 7, 11, 14, 17, 20, 23, 25, 28, 31, 35, 38, 39, 42, 45, 49]
+        );
+    }
+
+    #[test]
+    fn adapters_between_close_neighbours_are_removable() {
+        assert_eq!(chain(SMALL_EXAMPLE).removable(), vec![5, 6, 11]);
+        assert_eq!(
+            chain(LARGE_EXAMPLE).removable(),
+            vec![1, 2, 3, 8, 9, 10, 18, 19, 24, 32, 33, 34, 46, 47, 48]
+        );
+    }
+}
@@ -1,63 +1,68 @@
-use std::collections::HashMap;
 use std::str::FromStr;
 
-fn count_combinations(adapters: &[i64]) -> i64 {
-    let mut counts: HashMap<i64, i64> = HashMap::new();
+mod chain;
 
-    // Start with our initial value
-    counts.insert(0, 1);
-
-    let end = *adapters.last().unwrap();
-
-    // Iterate dynamically
-    for i in 1..=end {
-        if !adapters.contains(&i) {
-            continue;
-        }
-
-        // Find the routes that we could have come from
-        let routes: i64 = (1..=3)
-            .filter(|j| counts.contains_key(&(i - j)))
-            .map(|j| counts[&(i - j)])
-            .sum();
-
-        counts.insert(i, routes);
-    }
-
-    counts[&end]
-}
+use chain::AdapterChain;
 
 fn main() {
     let input = std::fs::read_to_string("input.txt").unwrap();
-    let mut formatted: Vec<_> = input
+    let adapters: Vec<_> = input
         .trim()
         .lines()
         .map(|l| i64::from_str(l).unwrap())
         .collect();
 
-    formatted.sort();
-
-    let one_gaps = formatted
-        .windows(2)
-        .filter(|window| match window {
-            &[a, b] => b - a == 1,
-            _ => unreachable!(),
-        })
-        .count()
-        + 1;
-
-    let three_gaps = formatted
-        .windows(2)
-        .filter(|window| match window {
-            &[a, b] => b - a == 3,
-            _ => unreachable!(),
-        })
-        .count()
-        + 1;
+    let args: Vec<_> = std::env::args().skip(1).collect();
+
+    let tolerance = args
+        .first()
+        .map(|x| u16::from_str(x).expect("Tolerance should be a number up to 65535"))
+        .unwrap_or(3);
+
+    let chain = AdapterChain::with_tolerance(adapters, tolerance).unwrap();
+
+    match args.get(1).map(String::as_str) {
+        Some("list") => {
+            for arrangement in chain.arrangements() {
+                println!("{:?}", arrangement);
+            }
+
+            return;
+        }
+        Some("analyse") => {
+            let minimal = chain.minimal_subset();
+
+            println!("Minimal subset ({} adapters): {:?}", minimal.len(), minimal);
+            println!("Mandatory adapters: {:?}", chain.mandatory());
+            println!("Individually removable adapters: {:?}", chain.removable());
+
+            return;
+        }
+        Some("sample") => {
+            let count = args
+                .get(2)
+                .map(|x| usize::from_str(x).expect("Sample count should be a number"))
+                .unwrap_or(1);
+
+            let mut rng = rand::thread_rng();
+
+            for _ in 0..count {
+                println!("{:?}", chain.sample(&mut rng));
+            }
+
+            return;
+        }
+        _ => (),
+    }
+
+    let gaps = chain.gaps();
+
+    let one_gaps = gaps.get(&1).copied().unwrap_or_default();
+    let three_gaps = gaps.get(&3).copied().unwrap_or_default();
 
     println!("Part 1 Solution: {}", one_gaps * three_gaps);
 
-    let combinations = count_combinations(&formatted);
+    let combinations = chain.count_arrangements();
 
     println!("Part 2 Solution: {}", combinations);
 }
@@ -1,169 +1,242 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+mod rules;
+
+use rules::{Neighbourhood, Rules};
+
+const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Statistics about the seats after a generation has been simulated.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum Mode {
-    Relaxed,
-    Strict,
+struct Generation {
+    number: usize,
+    occupied: u32,
+    changed: usize,
+    /// An earlier generation with exactly the same layout, once the layout is found to repeat.
+    repeats: Option<usize>,
 }
 
 #[derive(Debug)]
 struct Automata {
-    state: Vec<Vec<char>>,
+    rules: Rules,
+    rows: usize,
+    cols: usize,
+    state: Vec<u8>,
+    next: Vec<u8>,
+    seats: Vec<usize>,
+    neighbours: Vec<Vec<usize>>,
+    generation: usize,
+    /// A single earlier layout to compare against, and the generation it was taken at.
+    snapshot: (Vec<u8>, usize),
+    /// How many generations to wait before moving the snapshot forward.
+    power: usize,
+    finished: bool,
 }
 
 impl Automata {
-    pub fn new(initial: Vec<Vec<char>>) -> Self {
-        Self { state: initial }
+    /// Creates the automata from a board, which must have the same number of cells in each row.
+    pub fn new(initial: Vec<Vec<char>>, rules: Rules) -> Result<Self, String> {
+        let rows = initial.len();
+        let cols = initial.first().map_or(0, Vec::len);
+
+        if let Some(row) = initial.iter().position(|row| row.len() != cols) {
+            return Err(format!(
+                "Row {} has {} cells, but the first row has {}",
+                row + 1,
+                initial[row].len(),
+                cols
+            ));
+        }
+        let state: Vec<_> = initial.iter().flatten().map(|c| *c as u8).collect();
+
+        let mut automata = Self {
+            rules,
+            rows,
+            cols,
+            next: state.clone(),
+            snapshot: (state.clone(), 0),
+            state,
+            seats: Vec::new(),
+            neighbours: Vec::new(),
+            generation: 0,
+            power: 1,
+            finished: false,
+        };
+
+        // Floor never changes, so only the seats need updating
+        automata.seats = (0..automata.state.len())
+            .filter(|i| automata.state[*i] != automata.rules.floor)
+            .collect();
+
+        automata.neighbours = automata
+            .seats
+            .iter()
+            .map(|i| automata.neighbours(*i))
+            .collect();
+
+        Ok(automata)
     }
 
-    pub fn simulate(&mut self, mode: Mode) -> u32 {
-        loop {
-            // Copy the state
-            let mut next = self.state.clone();
+    /// Runs until the layout stops changing, or fails if it starts repeating a longer cycle.
+    pub fn simulate(&mut self) -> Result<u32, String> {
+        let last = match self.last() {
+            Some(last) => last,
+            None => return Ok(self.count_all_occupied()),
+        };
+
+        match last.repeats {
+            Some(previous) if previous + 1 == last.number => Ok(last.occupied),
+            Some(previous) => Err(format!(
+                "Generation {} repeats generation {}, so the layout oscillates forever",
+                last.number, previous
+            )),
+            None => unreachable!(),
+        }
+    }
 
-            // Apply the rules to each space
-            for i in 0..self.state.len() {
-                for j in 0..self.state[i].len() {
-                    next[i][j] = self.calculate_next(i, j, mode);
-                }
-            }
+    /// Simulates a single generation, returning the number of seats that changed.
+    fn step(&mut self) -> usize {
+        let mut changed = 0;
+
+        for (seat, neighbours) in self.seats.iter().zip(&self.neighbours) {
+            let current = self.state[*seat];
+            let occupied = neighbours
+                .iter()
+                .filter(|n| self.state[**n] == self.rules.occupied)
+                .count();
 
-            if self.state == next {
-                break self.count_all_occupied();
+            let next = self.rules.next(current, occupied);
+
+            if next != current {
+                changed += 1;
             }
 
-            self.state = next;
+            self.next[*seat] = next;
         }
+
+        std::mem::swap(&mut self.state, &mut self.next);
+        self.generation += 1;
+
+        changed
     }
 
-    fn calculate_next(&self, x: usize, y: usize, mode: Mode) -> char {
-        let current = self.state[x][y];
-        let occupied = self.count_occupied(x, y, mode);
+    /// Moves from a cell in a direction, returning `None` if it leaves the grid.
+    fn offset(&self, index: usize, (dx, dy): (isize, isize), distance: usize) -> Option<usize> {
+        let (x, y) = (index / self.cols, index % self.cols);
 
-        if current == 'L' && occupied == 0 {
-            '#'
-        } else if current == '#' && 4 <= occupied && mode == Mode::Relaxed {
-            'L'
-        } else if current == '#' && 5 <= occupied && mode == Mode::Strict {
-            'L'
-        } else {
-            current
+        let x = x as isize + dx * distance as isize;
+        let y = y as isize + dy * distance as isize;
+
+        if x < 0 || y < 0 || x as usize >= self.rows || y as usize >= self.cols {
+            return None;
         }
+
+        Some(x as usize * self.cols + y as usize)
     }
 
-    fn at(&self, x: usize, y: usize) -> Option<char> {
-        self.state
-            .get(x)
-            .map(|row| row.get(y))
-            .flatten()
-            .map(|c| *c)
+    /// Finds the nearest seat in a direction, looking no further than `limit` cells away.
+    fn visible(&self, index: usize, direction: (isize, isize), limit: usize) -> Option<usize> {
+        (1..=limit)
+            .map_while(|distance| self.offset(index, direction, distance))
+            .find(|i| self.state[*i] != self.rules.floor)
     }
 
-    fn is_occupied(&self, x: usize, y: usize) -> bool {
-        self.at(x, y) == Some('#')
+    /// Finds the seats whose occupancy affects the seat at `index`.
+    fn neighbours(&self, index: usize) -> Vec<usize> {
+        // Rays stop at the edge of the grid, so they never need to be longer than it
+        let limit = match self.rules.neighbourhood {
+            Neighbourhood::Adjacent => 1,
+            Neighbourhood::LineOfSight => self.rows.max(self.cols),
+            Neighbourhood::Radius(radius) => return self.within(index, radius),
+        };
+
+        DIRECTIONS
+            .iter()
+            .filter_map(|direction| self.visible(index, *direction, limit))
+            .collect()
     }
 
-    pub fn count_occupied(&self, x: usize, y: usize, mode: Mode) -> usize {
-        let mut total = 0;
+    /// Finds every seat within `radius` steps of the seat at `index`.
+    fn within(&self, index: usize, radius: usize) -> Vec<usize> {
+        let (x, y) = (index / self.cols, index % self.cols);
 
-        let lx = if x >= 1 { x - 1 } else { 0 };
-        let ly = if y >= 1 { y - 1 } else { 0 };
+        let rows = x.saturating_sub(radius)..=x.saturating_add(radius).min(self.rows - 1);
+        let cols = y.saturating_sub(radius)..=y.saturating_add(radius).min(self.cols - 1);
 
-        if mode == Mode::Relaxed {
-            for i in lx..=x + 1 {
-                for j in ly..=y + 1 {
-                    if i == x && j == y {
-                        continue;
-                    }
+        rows.flat_map(|i| cols.clone().map(move |j| i * self.cols + j))
+            .filter(|i| *i != index && self.state[*i] != self.rules.floor)
+            .collect()
+    }
 
-                    if self.is_occupied(i, j) {
-                        total += 1;
-                    }
-                }
-            }
+    fn count_all_occupied(&self) -> u32 {
+        self.state
+            .iter()
+            .filter(|c| **c == self.rules.occupied)
+            .count() as u32
+    }
+}
 
-            return total;
-        }
+impl Iterator for Automata {
+    type Item = Generation;
 
-        let rows = self.state.len();
-        let cols = self.state[0].len();
-
-        // Mode is Mode::Strict, begin counting vertically
-        if let Some('#') = (0..x)
-            .rev()
-            .filter_map(|i| self.at(i, y))
-            .filter(|c| *c != '.')
-            .next()
-        {
-            total += 1;
+    /// Simulates the next generation, stopping once the layout is found to repeat.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
         }
 
-        if let Some('#') = (x + 1..rows)
-            .filter_map(|i| self.at(i, y))
-            .filter(|c| *c != '.')
-            .next()
-        {
-            total += 1;
-        }
+        let changed = self.step();
 
-        // Count horizontally
-        if let Some('#') = (0..y)
-            .rev()
-            .filter_map(|i| self.at(x, i))
-            .filter(|c| *c != '.')
-            .next()
-        {
-            total += 1;
-        }
+        // A layout with no changes repeats the previous one, which is the common case
+        let repeats = if changed == 0 {
+            Some(self.generation - 1)
+        } else if self.state == self.snapshot.0 {
+            Some(self.snapshot.1)
+        } else {
+            // Brent's algorithm moves the snapshot forward after doubling waits, so a cycle is
+            // found without storing every layout
+            if self.generation - self.snapshot.1 == self.power {
+                self.snapshot = (self.state.clone(), self.generation);
+                self.power *= 2;
+            }
 
-        if let Some('#') = (y + 1..cols)
-            .filter_map(|i| self.at(x, i))
-            .filter(|c| *c != '.')
-            .next()
-        {
-            total += 1;
-        }
+            None
+        };
 
-        // Count the lower left to upper right diagonal
-        if let Some('#') = (1..100)
-            .filter_map(|i| self.at(x - i, y + i))
-            .filter(|c| *c != '.')
-            .next()
-        {
-            total += 1;
-        }
+        self.finished = repeats.is_some();
 
-        if let Some('#') = (1..100)
-            .filter_map(|i| self.at(x + i, y - i))
-            .filter(|c| *c != '.')
-            .next()
-        {
-            total += 1;
-        }
+        Some(Generation {
+            number: self.generation,
+            occupied: self.count_all_occupied(),
+            changed,
+            repeats,
+        })
+    }
+}
 
-        // Count the upper left to lower right diagonal
-        if let Some('#') = (1..100)
-            .filter_map(|i| self.at(x + i, y + i))
-            .filter(|c| *c != '.')
-            .next()
-        {
-            total += 1;
+impl fmt::Display for Automata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.cols == 0 {
+            return Ok(());
         }
 
-        if let Some('#') = (1..100)
-            .filter_map(|i| self.at(x - i, y - i))
-            .filter(|c| *c != '.')
-            .next()
-        {
-            total += 1;
+        for row in self.state.chunks(self.cols) {
+            writeln!(f, "{}", String::from_utf8_lossy(row))?;
         }
 
-        total
-    }
-
-    fn count_all_occupied(&self) -> u32 {
-        self.state
-            .iter()
-            .map(|row| row.iter().filter(|c| **c == '#').count() as u32)
-            .sum()
+        Ok(())
     }
 }
 
@@ -172,16 +245,326 @@ pub fn get_board(filename: &str) -> Vec<Vec<char>> {
     input.trim().lines().map(|l| l.chars().collect()).collect()
 }
 
+/// Prints or saves each generation as it is simulated.
+fn watch(mut automata: Automata, delay: Option<Duration>, frames: Option<PathBuf>) {
+    let save = |number: usize, automata: &Automata| {
+        if let Some(directory) = &frames {
+            let path = directory.join(format!("generation-{:04}.txt", number));
+            std::fs::write(path, automata.to_string()).unwrap();
+        }
+    };
+
+    if let Some(directory) = &frames {
+        std::fs::create_dir_all(directory).unwrap();
+    }
+
+    save(0, &automata);
+
+    while let Some(generation) = automata.next() {
+        save(generation.number, &automata);
+
+        if let Some(delay) = delay {
+            // Clear the terminal and draw the frame from the top left
+            print!("\x1b[2J\x1b[H{}", automata);
+            std::thread::sleep(delay);
+        }
+
+        println!(
+            "Generation {}: {} occupied, {} changed",
+            generation.number, generation.occupied, generation.changed
+        );
+
+        match generation.repeats {
+            Some(previous) if previous + 1 == generation.number => println!("Layout is stable"),
+            Some(previous) => println!(
+                "Layout oscillates with period {}",
+                generation.number - previous
+            ),
+            None => (),
+        }
+    }
+}
+
 fn main() {
     let board = get_board("input.txt");
 
-    let mut automata = Automata::new(board.clone());
-    let occupied = automata.simulate(Mode::Relaxed);
+    let mut args = std::env::args().skip(1);
+    let mut rules = Vec::new();
+    let mut delay = None;
+    let mut frames = None;
+    let mut history = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--animate" => {
+                let millis = args.next().map(|x| u64::from_str(&x));
+                let millis = millis.expect("Missing frame delay").unwrap();
+                delay = Some(Duration::from_millis(millis));
+            }
+            "--frames" => frames = Some(PathBuf::from(args.next().expect("Missing directory"))),
+            "--history" => history = true,
+            _ => rules.push(arg),
+        }
+    }
+
+    let rules = if rules.is_empty() {
+        None
+    } else {
+        Some(Rules::from_str(&rules.join(" ")).unwrap())
+    };
+
+    if history || delay.is_some() || frames.is_some() {
+        let rules = rules.unwrap_or_else(Rules::relaxed);
+        watch(Automata::new(board, rules).unwrap(), delay, frames);
+        return;
+    }
+
+    if let Some(rules) = rules {
+        let mut automata = Automata::new(board, rules).unwrap();
+
+        println!("Occupied: {}", automata.simulate().unwrap());
+        return;
+    }
+
+    let mut automata = Automata::new(board.clone(), Rules::relaxed()).unwrap();
+    let occupied = automata.simulate().unwrap();
 
     println!("Part 1 Solution: {}", occupied);
 
-    let mut automata = Automata::new(board);
-    let occupied = automata.simulate(Mode::Strict);
+    let mut automata = Automata::new(board, Rules::strict()).unwrap();
+    let occupied = automata.simulate().unwrap();
 
     println!("Part 2 Solution: {}", occupied);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(rows: usize, cols: usize, seats: &[(usize, usize)]) -> Vec<Vec<char>> {
+        let mut board = vec![vec!['.'; cols]; rows];
+
+        for (x, y) in seats {
+            board[*x][*y] = 'L';
+        }
+
+        board
+    }
+
+    #[test]
+    fn seats_are_visible_across_large_grids() {
+        let seats = [(0, 0), (0, 149), (149, 0), (149, 149), (74, 75), (149, 75)];
+        let automata = Automata::new(board(150, 150, &seats), Rules::strict()).unwrap();
+
+        let mut neighbours = automata.neighbours(0);
+        neighbours.sort_unstable();
+
+        // The opposite corner is 149 cells away diagonally
+        assert_eq!(neighbours, vec![149, 149 * 150, 149 * 150 + 149]);
+
+        let mut neighbours = automata.neighbours(74 * 150 + 75);
+        neighbours.sort_unstable();
+
+        // The anti-diagonal reaches the bottom left corner, 75 cells away
+        assert_eq!(neighbours, vec![149, 149 * 150, 149 * 150 + 75]);
+    }
+
+    #[test]
+    fn seats_on_the_edges_only_see_inside_the_grid() {
+        let seats = [(0, 0), (0, 1), (1, 0), (1, 1), (2, 2)];
+        let relaxed = Automata::new(board(3, 3, &seats), Rules::relaxed()).unwrap();
+
+        let mut neighbours = relaxed.neighbours(0);
+        neighbours.sort_unstable();
+        assert_eq!(neighbours, vec![1, 3, 4]);
+
+        let strict = Automata::new(board(3, 3, &seats), Rules::strict()).unwrap();
+
+        let mut strict = strict.neighbours(8);
+        strict.sort_unstable();
+        assert_eq!(strict, vec![4]);
+
+        assert!(Automata::new(board(1, 1, &[(0, 0)]), Rules::strict())
+            .unwrap()
+            .neighbours(0)
+            .is_empty());
+    }
+
+    #[test]
+    fn seats_along_a_long_diagonal_can_all_be_occupied() {
+        let seats: Vec<_> = (0..120).map(|i| (i, i)).collect();
+        let mut automata = Automata::new(board(120, 120, &seats), Rules::strict()).unwrap();
+
+        // Each seat sees at most 2 others along the diagonal, so everyone can sit down
+        assert_eq!(automata.simulate(), Ok(120));
+    }
+
+    #[test]
+    fn a_radius_of_one_matches_adjacent_seats() {
+        let seats: Vec<_> = (0..5)
+            .flat_map(|x| (0..5).map(move |y| (x, y)))
+            .filter(|(x, y)| (x * 5 + y) % 3 != 0)
+            .collect();
+
+        let adjacent = Automata::new(board(5, 5, &seats), Rules::relaxed()).unwrap();
+        let radius = Rules {
+            neighbourhood: Neighbourhood::Radius(1),
+            ..Rules::relaxed()
+        };
+        let radius = Automata::new(board(5, 5, &seats), radius).unwrap();
+
+        for i in 0..25 {
+            let mut expected = adjacent.neighbours(i);
+            expected.sort_unstable();
+
+            assert_eq!(radius.neighbours(i), expected, "neighbours of {}", i);
+        }
+    }
+
+    #[test]
+    fn huge_radii_cover_the_whole_grid() {
+        let seats = [(0, 0), (0, 2), (2, 1)];
+        let rules = Rules {
+            neighbourhood: Neighbourhood::Radius(usize::MAX),
+            ..Rules::relaxed()
+        };
+        let automata = Automata::new(board(3, 3, &seats), rules).unwrap();
+
+        assert_eq!(automata.neighbours(7), vec![0, 2]);
+        assert_eq!(automata.neighbours(0), vec![2, 7]);
+    }
+
+    const EXAMPLE: &[&str] = &[
+        "L.LL.LL.LL",
+        "LLLLLLL.LL",
+        "L.L.L..L..",
+        "LLLL.LL.LL",
+        "L.LL.LL.LL",
+        "L.LLLLL.LL",
+        "..L.L.....",
+        "LLLLLLLLLL",
+        "L.LLLLLL.L",
+        "L.LLLLL.LL",
+    ];
+
+    fn example() -> Vec<Vec<char>> {
+        EXAMPLE.iter().map(|l| l.chars().collect()).collect()
+    }
+
+    #[test]
+    fn generations_report_their_statistics() {
+        let generations: Vec<_> = Automata::new(example(), Rules::relaxed())
+            .unwrap()
+            .collect();
+
+        let occupied: Vec<_> = generations.iter().map(|g| g.occupied).collect();
+        assert_eq!(occupied, vec![71, 20, 51, 30, 37, 37]);
+
+        let changed: Vec<_> = generations.iter().map(|g| g.changed).collect();
+        assert_eq!(changed, vec![71, 51, 31, 21, 7, 0]);
+
+        let numbers: Vec<_> = generations.iter().map(|g| g.number).collect();
+        assert_eq!(numbers, vec![1, 2, 3, 4, 5, 6]);
+
+        let repeats: Vec<_> = generations.iter().map(|g| g.repeats).collect();
+        assert_eq!(repeats, vec![None, None, None, None, None, Some(5)]);
+    }
+
+    #[test]
+    fn examples_stabilise() {
+        let mut relaxed = Automata::new(example(), Rules::relaxed()).unwrap();
+        assert_eq!(relaxed.simulate(), Ok(37));
+        assert_eq!(relaxed.next(), None);
+
+        let mut strict = Automata::new(example(), Rules::strict()).unwrap();
+        assert_eq!(strict.simulate(), Ok(26));
+    }
+
+    #[test]
+    fn oscillating_layouts_are_detected() {
+        // Every seat flips each generation, like a blinker
+        let blinker = Rules {
+            birth: 0..=8,
+            survival: 9..=9,
+            ..Rules::relaxed()
+        };
+
+        let mut automata =
+            Automata::new(board(1, 3, &[(0, 0), (0, 1), (0, 2)]), blinker.clone()).unwrap();
+
+        let first = automata.next().unwrap();
+        assert_eq!((first.occupied, first.changed, first.repeats), (3, 3, None));
+        assert_eq!(automata.to_string(), "###\n");
+
+        let second = automata.next().unwrap();
+        assert_eq!(
+            (second.occupied, second.changed, second.repeats),
+            (0, 3, None)
+        );
+        assert_eq!(automata.to_string(), "LLL\n");
+
+        // Only the snapshot from generation 1 is kept, so the repeat is found against it
+        let third = automata.next().unwrap();
+        assert_eq!(
+            (third.occupied, third.changed, third.repeats),
+            (3, 3, Some(1))
+        );
+
+        assert_eq!(automata.next(), None);
+
+        let mut automata = Automata::new(board(1, 3, &[(0, 0), (0, 1), (0, 2)]), blinker).unwrap();
+        assert_eq!(
+            automata.simulate(),
+            Err(String::from(
+                "Generation 3 repeats generation 1, so the layout oscillates forever"
+            ))
+        );
+    }
+
+    #[test]
+    fn long_oscillations_report_their_period() {
+        // Seats fill when nobody is next to them and stay filled with exactly one neighbour,
+        // which takes 3 generations to settle into a cycle of 6
+        let rules = Rules {
+            birth: 0..=0,
+            survival: 1..=1,
+            ..Rules::relaxed()
+        };
+
+        let board = vec!["LLLLLLL#".chars().collect()];
+        let generations: Vec<_> = Automata::new(board.clone(), rules.clone())
+            .unwrap()
+            .collect();
+
+        let last = generations.last().unwrap();
+        assert_eq!((last.number, last.repeats), (13, Some(7)));
+
+        let mut automata = Automata::new(board, rules).unwrap();
+        assert_eq!(
+            automata.simulate(),
+            Err(String::from(
+                "Generation 13 repeats generation 7, so the layout oscillates forever"
+            ))
+        );
+        assert_eq!(automata.to_string(), "LL##L###\n");
+    }
+
+    #[test]
+    fn empty_boards_are_already_stable() {
+        let mut automata = Automata::new(Vec::new(), Rules::relaxed()).unwrap();
+
+        assert_eq!(automata.to_string(), "");
+        assert_eq!(automata.simulate(), Ok(0));
+    }
+
+    #[test]
+    fn ragged_boards_are_rejected() {
+        let mut ragged = board(3, 3, &[(0, 0), (1, 1)]);
+        ragged[2].push(' ');
+
+        assert_eq!(
+            Automata::new(ragged, Rules::relaxed()).unwrap_err(),
+            "Row 3 has 4 cells, but the first row has 3"
+        );
+    }
+}
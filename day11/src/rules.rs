@@ -0,0 +1,189 @@
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Which cells count as neighbours of a seat.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Neighbourhood {
+    /// The 8 cells touching the seat.
+    Adjacent,
+    /// The nearest seat in each of the 8 directions.
+    LineOfSight,
+    /// Every cell within the given number of steps, including diagonally.
+    Radius(usize),
+}
+
+/// The rules for how seats change between generations.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rules {
+    pub neighbourhood: Neighbourhood,
+    /// The numbers of occupied neighbours that cause an empty seat to become occupied.
+    pub birth: RangeInclusive<usize>,
+    /// The numbers of occupied neighbours that allow an occupied seat to stay occupied.
+    pub survival: RangeInclusive<usize>,
+    pub floor: u8,
+    pub empty: u8,
+    pub occupied: u8,
+}
+
+impl Rules {
+    /// People sit if no adjacent seats are taken and leave if 4 or more are.
+    pub fn relaxed() -> Self {
+        Self {
+            neighbourhood: Neighbourhood::Adjacent,
+            birth: 0..=0,
+            survival: 0..=3,
+            floor: b'.',
+            empty: b'L',
+            occupied: b'#',
+        }
+    }
+
+    /// People sit if no visible seats are taken and leave if 5 or more are.
+    pub fn strict() -> Self {
+        Self {
+            neighbourhood: Neighbourhood::LineOfSight,
+            survival: 0..=4,
+            ..Self::relaxed()
+        }
+    }
+
+    pub fn next(&self, current: u8, occupied: usize) -> u8 {
+        if current == self.empty && self.birth.contains(&occupied) {
+            self.occupied
+        } else if current == self.occupied && !self.survival.contains(&occupied) {
+            self.empty
+        } else {
+            current
+        }
+    }
+}
+
+fn parse_range(s: &str) -> Result<RangeInclusive<usize>, String> {
+    let invalid = |_| format!("Invalid range: {}", s);
+
+    match s.split_once('-') {
+        Some((start, end)) => {
+            Ok(usize::from_str(start).map_err(invalid)?..=usize::from_str(end).map_err(invalid)?)
+        }
+        None => usize::from_str(s).map(|v| v..=v).map_err(invalid),
+    }
+}
+
+impl FromStr for Rules {
+    type Err = String;
+
+    /// Parses rules such as `sight B0 S0-4` or `radius:2 B0-1 S0-8 .L#`, where the last part
+    /// optionally gives the floor, empty and occupied characters.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split_whitespace().collect();
+
+        let (neighbourhood, birth, survival, chars) = match parts.as_slice() {
+            [n, b, s] => (n, b, s, ".L#"),
+            [n, b, s, c] => (n, b, s, *c),
+            _ => {
+                return Err(format!(
+                    "Expected a neighbourhood, birth and survival: {}",
+                    s
+                ))
+            }
+        };
+
+        let neighbourhood = match *neighbourhood {
+            "adjacent" => Neighbourhood::Adjacent,
+            "sight" => Neighbourhood::LineOfSight,
+            n => match n.strip_prefix("radius:").map(usize::from_str) {
+                Some(Ok(radius)) => Neighbourhood::Radius(radius),
+                _ => return Err(format!("Unknown neighbourhood: {}", n)),
+            },
+        };
+
+        let birth = birth
+            .strip_prefix('B')
+            .ok_or(format!("Birth should start with B: {}", birth))?;
+
+        let survival = survival
+            .strip_prefix('S')
+            .ok_or(format!("Survival should start with S: {}", survival))?;
+
+        let (floor, empty, occupied) = match chars.as_bytes() {
+            [floor, empty, occupied] => (*floor, *empty, *occupied),
+            _ => return Err(format!("Expected 3 characters for the cells: {}", chars)),
+        };
+
+        Ok(Self {
+            neighbourhood,
+            birth: parse_range(birth)?,
+            survival: parse_range(survival)?,
+            floor,
+            empty,
+            occupied,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_can_be_parsed() {
+        assert_eq!(Rules::from_str("adjacent B0 S0-3"), Ok(Rules::relaxed()));
+        assert_eq!(Rules::from_str("sight B0 S0-4 .L#"), Ok(Rules::strict()));
+
+        let expected = Rules {
+            neighbourhood: Neighbourhood::Radius(2),
+            birth: 3..=3,
+            survival: 2..=3,
+            floor: b'_',
+            empty: b'o',
+            occupied: b'x',
+        };
+
+        assert_eq!(Rules::from_str("  radius:2   B3 S2-3 _ox "), Ok(expected));
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let cases = [
+            (
+                "adjacent B0",
+                "Expected a neighbourhood, birth and survival: adjacent B0",
+            ),
+            (
+                "adjacent B0 S0 .L# x",
+                "Expected a neighbourhood, birth and survival: adjacent B0 S0 .L# x",
+            ),
+            ("hexagonal B0 S0", "Unknown neighbourhood: hexagonal"),
+            ("radius:x B0 S0", "Unknown neighbourhood: radius:x"),
+            ("radius:-1 B0 S0", "Unknown neighbourhood: radius:-1"),
+            ("adjacent 0 S0", "Birth should start with B: 0"),
+            ("adjacent B0 B0", "Survival should start with S: B0"),
+            ("adjacent B0-x S0", "Invalid range: 0-x"),
+            ("adjacent B0 S-1", "Invalid range: -1"),
+            (
+                "adjacent B0 S0 .L",
+                "Expected 3 characters for the cells: .L",
+            ),
+        ];
+
+        for (input, error) in &cases {
+            assert_eq!(
+                Rules::from_str(input),
+                Err(String::from(*error)),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn seats_change_according_to_their_neighbours() {
+        let rules = Rules::relaxed();
+
+        assert_eq!(rules.next(b'L', 0), b'#');
+        assert_eq!(rules.next(b'L', 1), b'L');
+        assert_eq!(rules.next(b'#', 3), b'#');
+        assert_eq!(rules.next(b'#', 4), b'L');
+        assert_eq!(rules.next(b'.', 0), b'.');
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::grid::Grid;
+use crate::Rotation;
+
+/// A single navigation instruction, such as `N3`, `L90` or `F10`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action<G: Grid> {
+    Move(G::Heading, i32),
+    Turn(Rotation),
+    Forward(i32),
+}
+
+impl<G: Grid> FromStr for Action<G> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(String::from("Empty instruction"));
+        }
+
+        // Headings on some grids take more than one letter, such as `NE`
+        let split = s
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(s.len());
+        let (kind, value) = s.split_at(split);
+
+        let value =
+            i32::from_str(value).map_err(|_| format!("Invalid value in instruction: {}", s))?;
+
+        match kind {
+            "L" => Ok(Action::Turn(Rotation::anticlockwise::<G>(value)?)),
+            "R" => Ok(Action::Turn(Rotation::clockwise::<G>(value)?)),
+            "F" => Ok(Action::Forward(value)),
+            _ => G::Heading::from_str(kind)
+                .map(|heading| Action::Move(heading, value))
+                .map_err(|_| format!("Unknown action: {}", kind)),
+        }
+    }
+}
+
+impl<G: Grid> fmt::Display for Action<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Action::Move(heading, value) => write!(f, "{}{}", heading, value),
+            Action::Turn(rotation) => {
+                let degrees = rotation.turns * G::TURN;
+
+                // Turns of more than half a circle are more naturally written as a left turn
+                if degrees > 180 {
+                    write!(f, "L{}", 360 - degrees)
+                } else {
+                    write!(f, "R{}", degrees)
+                }
+            }
+            Action::Forward(value) => write!(f, "F{}", value),
+        }
+    }
+}
+
+/// Parses one action per line, reporting the line number of anything invalid.
+pub fn parse_actions<G: Grid>(input: &str) -> Result<Vec<Action<G>>, String> {
+    input
+        .trim()
+        .lines()
+        .enumerate()
+        .map(|(i, line)| Action::from_str(line).map_err(|e| format!("Line {}: {}", i + 1, e)))
+        .collect()
+}
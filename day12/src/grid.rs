@@ -0,0 +1,70 @@
+use std::fmt;
+use std::ops::{AddAssign, Mul};
+use std::str::FromStr;
+
+use crate::{Coordinate, Heading, Rotation};
+
+/// A grid that the navigation instructions can be followed on.
+pub trait Grid: Copy + fmt::Debug + Eq {
+    type Heading: Copy + fmt::Debug + fmt::Display + Eq + FromStr<Err = String>;
+    type Coordinate: Copy
+        + fmt::Debug
+        + Default
+        + Eq
+        + AddAssign
+        + Mul<i32, Output = Self::Coordinate>;
+
+    /// The smallest angle that can be turned, in degrees.
+    const TURN: i32;
+    /// The heading that the ship starts with.
+    const HEADING: Self::Heading;
+
+    /// Gets where the waypoint starts, relative to the ship.
+    fn waypoint() -> Self::Coordinate;
+
+    /// Gets the offset from moving `value` steps towards `heading`.
+    fn modifier(heading: Self::Heading, value: i32) -> Self::Coordinate;
+
+    /// Turns a heading clockwise by a single turn.
+    fn turn(heading: Self::Heading) -> Self::Heading;
+
+    /// Turns a coordinate clockwise about the origin by a single turn.
+    fn turn_coordinate(coordinate: Self::Coordinate) -> Self::Coordinate;
+
+    /// Gets the number of steps needed to get from the origin to `coordinate`.
+    fn distance(coordinate: Self::Coordinate) -> i32;
+}
+
+/// The square grid from the puzzle, with the four compass headings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Square;
+
+impl Grid for Square {
+    type Heading = Heading;
+    type Coordinate = Coordinate;
+
+    const TURN: i32 = 90;
+    const HEADING: Heading = Heading::East;
+
+    fn waypoint() -> Coordinate {
+        Coordinate { x: 10, y: 1 }
+    }
+
+    fn modifier(heading: Heading, value: i32) -> Coordinate {
+        heading.modifier(value)
+    }
+
+    fn turn(mut heading: Heading) -> Heading {
+        heading.rotate(Rotation { turns: 1 });
+        heading
+    }
+
+    fn turn_coordinate(mut coordinate: Coordinate) -> Coordinate {
+        coordinate.rotate(Rotation { turns: 1 });
+        coordinate
+    }
+
+    fn distance(coordinate: Coordinate) -> i32 {
+        coordinate.x.abs() + coordinate.y.abs()
+    }
+}
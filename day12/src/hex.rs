@@ -0,0 +1,130 @@
+use std::fmt;
+use std::ops::{AddAssign, Mul};
+use std::str::FromStr;
+
+use crate::grid::Grid;
+
+/// A grid of flat topped hexagons, where each turn is 60 degrees.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Hex;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HexHeading {
+    North,
+    NorthEast,
+    SouthEast,
+    South,
+    SouthWest,
+    NorthWest,
+}
+
+const HEADINGS: [HexHeading; 6] = [
+    HexHeading::North,
+    HexHeading::NorthEast,
+    HexHeading::SouthEast,
+    HexHeading::South,
+    HexHeading::SouthWest,
+    HexHeading::NorthWest,
+];
+
+impl FromStr for HexHeading {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HEADINGS
+            .iter()
+            .find(|h| h.to_string() == s)
+            .copied()
+            .ok_or_else(|| format!("Unknown heading: {}", s))
+    }
+}
+
+impl fmt::Display for HexHeading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match *self {
+            HexHeading::North => "N",
+            HexHeading::NorthEast => "NE",
+            HexHeading::SouthEast => "SE",
+            HexHeading::South => "S",
+            HexHeading::SouthWest => "SW",
+            HexHeading::NorthWest => "NW",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+/// A position in cube coordinates, which always satisfy `x + y + z == 0`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cube {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Mul<i32> for Cube {
+    type Output = Self;
+
+    fn mul(self, value: i32) -> Self {
+        Self {
+            x: self.x * value,
+            y: self.y * value,
+            z: self.z * value,
+        }
+    }
+}
+
+impl AddAssign for Cube {
+    fn add_assign(&mut self, other: Self) {
+        *self = Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        };
+    }
+}
+
+impl Grid for Hex {
+    type Heading = HexHeading;
+    type Coordinate = Cube;
+
+    const TURN: i32 = 60;
+    const HEADING: HexHeading = HexHeading::NorthEast;
+
+    fn waypoint() -> Cube {
+        let mut waypoint = Self::modifier(HexHeading::NorthEast, 10);
+        waypoint += Self::modifier(HexHeading::North, 1);
+        waypoint
+    }
+
+    fn modifier(heading: HexHeading, value: i32) -> Cube {
+        let (x, y, z) = match heading {
+            HexHeading::North => (0, 1, -1),
+            HexHeading::NorthEast => (1, 0, -1),
+            HexHeading::SouthEast => (1, -1, 0),
+            HexHeading::South => (0, -1, 1),
+            HexHeading::SouthWest => (-1, 0, 1),
+            HexHeading::NorthWest => (-1, 1, 0),
+        };
+
+        Cube { x, y, z } * value
+    }
+
+    fn turn(heading: HexHeading) -> HexHeading {
+        let index = HEADINGS.iter().position(|h| *h == heading).unwrap();
+        HEADINGS[(index + 1) % HEADINGS.len()]
+    }
+
+    fn turn_coordinate(coordinate: Cube) -> Cube {
+        // A clockwise sixth of a turn rotates the axes and negates them
+        Cube {
+            x: -coordinate.z,
+            y: -coordinate.x,
+            z: -coordinate.y,
+        }
+    }
+
+    fn distance(coordinate: Cube) -> i32 {
+        (coordinate.x.abs() + coordinate.y.abs() + coordinate.z.abs()) / 2
+    }
+}
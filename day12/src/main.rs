@@ -1,14 +1,59 @@
+use std::fmt;
 use std::ops::{AddAssign, Mul};
 use std::str::FromStr;
 
+mod action;
+mod grid;
+mod hex;
+mod route;
+mod space;
+
+use action::Action;
+use grid::{Grid, Square};
+use hex::Hex;
+use route::Route;
+use space::Space;
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum Mode {
     Normal,
     Waypoint,
 }
 
+/// A turn by a whole number of the grid's smallest angle, stored as clockwise turns.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-enum Heading {
+pub struct Rotation {
+    turns: i32,
+}
+
+impl Rotation {
+    fn turns<G: Grid>(degrees: i32) -> Result<i32, String> {
+        if degrees % G::TURN != 0 {
+            return Err(format!(
+                "Cannot turn by {} degrees, only multiples of {}",
+                degrees,
+                G::TURN
+            ));
+        }
+
+        Ok(degrees / G::TURN)
+    }
+
+    pub fn clockwise<G: Grid>(degrees: i32) -> Result<Self, String> {
+        Ok(Self {
+            turns: Self::turns::<G>(degrees)?.rem_euclid(360 / G::TURN),
+        })
+    }
+
+    pub fn anticlockwise<G: Grid>(degrees: i32) -> Result<Self, String> {
+        Ok(Self {
+            turns: (-Self::turns::<G>(degrees)?).rem_euclid(360 / G::TURN),
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Heading {
     North,
     East,
     South,
@@ -16,7 +61,7 @@ enum Heading {
 }
 
 impl Heading {
-    pub fn rotate(&mut self, degrees: i32) {
+    pub fn rotate(&mut self, rotation: Rotation) {
         let current = match *self {
             Heading::North => 0,
             Heading::East => 1,
@@ -24,7 +69,7 @@ impl Heading {
             Heading::West => 3,
         };
 
-        let updated = (current + degrees / 90) % 4;
+        let updated = (current + rotation.turns) % 4;
 
         *self = match updated {
             0 => Heading::North,
@@ -45,30 +90,47 @@ impl Heading {
     }
 }
 
+impl FromStr for Heading {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "N" => Ok(Heading::North),
+            "E" => Ok(Heading::East),
+            "S" => Ok(Heading::South),
+            "W" => Ok(Heading::West),
+            _ => Err(format!("Unknown heading: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Heading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = match *self {
+            Heading::North => 'N',
+            Heading::East => 'E',
+            Heading::South => 'S',
+            Heading::West => 'W',
+        };
+
+        write!(f, "{}", c)
+    }
+}
+
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
-struct Coordinate {
+pub struct Coordinate {
     pub x: i32,
     pub y: i32,
 }
 
 impl Coordinate {
-    pub fn update(&mut self, modifier: char, value: i32) {
-        match modifier {
-            'N' => self.y += value,
-            'E' => self.x += value,
-            'S' => self.y -= value,
-            'W' => self.x -= value,
-            _ => unreachable!(),
-        }
-    }
-
-    pub fn rotate(&mut self, degrees: i32) {
-        let s = (-degrees as f32).to_radians().sin() as i32;
-        let c = (-degrees as f32).to_radians().cos() as i32;
-
-        *self = Self {
-            x: self.x * c - self.y * s,
-            y: self.x * s + self.y * c,
+    pub fn rotate(&mut self, rotation: Rotation) {
+        // Apply the clockwise quarter turn matrix [[0, 1], [-1, 0]] the right number of times
+        for _ in 0..rotation.turns {
+            *self = Self {
+                x: self.y,
+                y: -self.x,
+            };
         }
     }
 }
@@ -94,88 +156,276 @@ impl AddAssign for Coordinate {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-struct Orienteer {
-    heading: Heading,
-    position: Coordinate,
-    waypoint: Coordinate,
+struct Orienteer<G: Grid> {
+    heading: G::Heading,
+    position: G::Coordinate,
+    waypoint: G::Coordinate,
 }
 
-impl Orienteer {
+impl<G: Grid> Orienteer<G> {
     pub fn new() -> Self {
         Default::default()
     }
 
-    pub fn simulate(&mut self, moves: &[(char, i32)], mode: Mode) -> Coordinate {
-        match mode {
-            Mode::Normal => self.simulate_normal(moves),
-            Mode::Waypoint => self.simulate_waypoint(moves),
-        }
-    }
-
-    fn simulate_normal(&mut self, moves: &[(char, i32)]) -> Coordinate {
-        for instruction in moves {
-            let (modifier, value) = *instruction;
-
-            match modifier {
-                'N' | 'E' | 'S' | 'W' => self.position.update(modifier, value),
-                'L' => self.heading.rotate(360 - value),
-                'R' => self.heading.rotate(value),
-                'F' => self.position += self.heading.modifier(value),
-                _ => unreachable!(),
-            }
+    pub fn simulate(&mut self, actions: &[Action<G>], mode: Mode) -> G::Coordinate {
+        for action in actions {
+            self.apply(*action, mode);
         }
 
         self.position
     }
 
-    fn simulate_waypoint(&mut self, moves: &[(char, i32)]) -> Coordinate {
-        for instruction in moves {
-            let (modifier, value) = *instruction;
+    /// Returns the state of the orienteer after each of the actions.
+    pub fn trace<'a>(
+        self,
+        actions: &'a [Action<G>],
+        mode: Mode,
+    ) -> impl Iterator<Item = Orienteer<G>> + 'a {
+        actions.iter().scan(self, move |state, action| {
+            state.apply(*action, mode);
+            Some(*state)
+        })
+    }
 
-            match modifier {
-                'N' | 'E' | 'S' | 'W' => self.waypoint.update(modifier, value),
-                'L' => self.waypoint.rotate(360 - value),
-                'R' => self.waypoint.rotate(value),
-                'F' => self.position += self.waypoint * value,
-                _ => unreachable!(),
+    fn apply(&mut self, action: Action<G>, mode: Mode) {
+        match (action, mode) {
+            (Action::Move(heading, value), Mode::Normal) => {
+                self.position += G::modifier(heading, value)
+            }
+            (Action::Turn(rotation), Mode::Normal) => {
+                for _ in 0..rotation.turns {
+                    self.heading = G::turn(self.heading);
+                }
+            }
+            (Action::Forward(value), Mode::Normal) => {
+                self.position += G::modifier(self.heading, value)
             }
+            (Action::Move(heading, value), Mode::Waypoint) => {
+                self.waypoint += G::modifier(heading, value)
+            }
+            (Action::Turn(rotation), Mode::Waypoint) => {
+                for _ in 0..rotation.turns {
+                    self.waypoint = G::turn_coordinate(self.waypoint);
+                }
+            }
+            (Action::Forward(value), Mode::Waypoint) => self.position += self.waypoint * value,
         }
-
-        self.position
     }
 }
 
-impl Default for Orienteer {
+impl<G: Grid> Default for Orienteer<G> {
     fn default() -> Self {
         Self {
-            heading: Heading::East,
+            heading: G::HEADING,
             position: Default::default(),
-            waypoint: Coordinate { x: 10, y: 1 },
+            waypoint: G::waypoint(),
         }
     }
 }
 
+/// Runs the actions on any grid, printing the distance travelled from the origin in each mode.
+fn navigate<G: Grid>(input: &str) {
+    let actions = action::parse_actions::<G>(input).unwrap();
+
+    for mode in &[Mode::Normal, Mode::Waypoint] {
+        let position = Orienteer::<G>::new().simulate(&actions, *mode);
+        println!(
+            "{:?}: {:?} at distance {}",
+            mode,
+            position,
+            G::distance(position)
+        );
+    }
+}
+
 fn main() {
     let input = std::fs::read_to_string("input.txt").unwrap();
+    let args: Vec<_> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("hex") => return navigate::<Hex>(&input),
+        Some("space") => return navigate::<Space>(&input),
+        _ => (),
+    }
 
-    let moves: Vec<_> = input
-        .trim()
-        .lines()
-        .map(|x| {
-            let modifier = x.chars().next().unwrap();
-            let value = i32::from_str(&x[1..]).unwrap();
+    let actions = action::parse_actions::<Square>(&input).unwrap();
 
-            (modifier, value)
-        })
-        .collect();
+    let mode = match args.get(1).map(String::as_str) {
+        None | Some("normal") => Mode::Normal,
+        Some("waypoint") => Mode::Waypoint,
+        Some(other) => panic!("Unknown mode: {}", other),
+    };
+
+    match args.first().map(String::as_str) {
+        Some("trace") => {
+            for (action, state) in actions
+                .iter()
+                .zip(Orienteer::<Square>::new().trace(&actions, mode))
+            {
+                println!(
+                    "{:<5} position = ({}, {}), heading = {}, waypoint = ({}, {})",
+                    action.to_string(),
+                    state.position.x,
+                    state.position.y,
+                    state.heading,
+                    state.waypoint.x,
+                    state.waypoint.y
+                );
+            }
+
+            return;
+        }
+        Some("stats") => {
+            println!("{}", Route::new(&actions, mode));
+            return;
+        }
+        Some("svg") => {
+            print!("{}", Route::new(&actions, mode).to_svg());
+            return;
+        }
+        _ => (),
+    }
+
+    let mut orienteer = Orienteer::<Square>::new();
+    let position = orienteer.simulate(&actions, Mode::Normal);
+
+    println!("Part 1 Solution: {}", Square::distance(position));
+
+    let mut orienteer = Orienteer::<Square>::new();
+    let position = orienteer.simulate(&actions, Mode::Waypoint);
+
+    println!("Part 2 Solution: {}", Square::distance(position));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn left_and_right_turns_compose(x in -1000..1000, y in -1000..1000, a in -20..20, b in -20..20) {
+            let mut composed = Coordinate { x, y };
+            composed.rotate(Rotation::clockwise::<Square>(a * 90).unwrap());
+            composed.rotate(Rotation::anticlockwise::<Square>(b * 90).unwrap());
+
+            let mut direct = Coordinate { x, y };
+            direct.rotate(Rotation::clockwise::<Square>((a - b) * 90).unwrap());
+
+            prop_assert_eq!(composed, direct);
+        }
+
+        #[test]
+        fn turning_back_is_the_identity(x in -1000..1000, y in -1000..1000, a in -20..20) {
+            let mut coordinate = Coordinate { x, y };
+            coordinate.rotate(Rotation::clockwise::<Square>(a * 90).unwrap());
+            coordinate.rotate(Rotation::anticlockwise::<Square>(a * 90).unwrap());
+
+            prop_assert_eq!(coordinate, Coordinate { x, y });
+        }
+
+        #[test]
+        fn headings_rotate_like_coordinates(a in -20..20, value in 1..100) {
+            let rotation = Rotation::clockwise::<Square>(a * 90).unwrap();
+
+            for heading in &[Heading::North, Heading::East, Heading::South, Heading::West] {
+                let mut rotated = *heading;
+                rotated.rotate(rotation);
+
+                let mut modifier = heading.modifier(value);
+                modifier.rotate(rotation);
 
-    let mut orienteer = Orienteer::new();
-    let position = orienteer.simulate(&moves, Mode::Normal);
+                prop_assert_eq!(rotated.modifier(value), modifier);
+            }
+        }
 
-    println!("Part 1 Solution: {}", position.x.abs() + position.y.abs());
+        #[test]
+        fn actions_can_be_round_tripped(kind in 0..7usize, value in 0..1000, turns in -4..8) {
+            let action: Action<Square> = match kind {
+                0 => Action::Move(Heading::North, value),
+                1 => Action::Move(Heading::East, value),
+                2 => Action::Move(Heading::South, value),
+                3 => Action::Move(Heading::West, value),
+                4 => Action::Turn(Rotation::anticlockwise::<Square>(turns * 90).unwrap()),
+                5 => Action::Turn(Rotation::clockwise::<Square>(turns * 90).unwrap()),
+                _ => Action::Forward(value),
+            };
+
+            prop_assert_eq!(action.to_string().parse::<Action<Square>>(), Ok(action));
+        }
+
+        #[test]
+        fn turns_that_are_not_right_angles_are_rejected(degrees in -1000..1000) {
+            prop_assume!(degrees % 90 != 0);
+
+            prop_assert!(Rotation::clockwise::<Square>(degrees).is_err());
+            prop_assert!(Rotation::anticlockwise::<Square>(degrees).is_err());
+        }
+
+        #[test]
+        fn hex_headings_rotate_like_coordinates(turns in 0..6, value in 1..100) {
+            for heading in &["N", "NE", "SE", "S", "SW", "NW"] {
+                let mut rotated = heading.parse().unwrap();
+                let mut modifier = Hex::modifier(rotated, value);
+
+                for _ in 0..turns {
+                    rotated = Hex::turn(rotated);
+                    modifier = Hex::turn_coordinate(modifier);
+                }
+
+                prop_assert_eq!(Hex::modifier(rotated, value), modifier);
+                prop_assert_eq!(Hex::distance(modifier), value);
+            }
+        }
+    }
 
-    let mut orienteer = Orienteer::new();
-    let position = orienteer.simulate(&moves, Mode::Waypoint);
+    #[test]
+    fn invalid_actions_are_reported_with_line_numbers() {
+        assert_eq!(
+            action::parse_actions::<Square>("F10\nN3\nL45\n"),
+            Err(String::from(
+                "Line 3: Cannot turn by 45 degrees, only multiples of 90"
+            ))
+        );
 
-    println!("Part 2 Solution: {}", position.x.abs() + position.y.abs());
+        assert_eq!(
+            action::parse_actions::<Square>("F10\nX3\n"),
+            Err(String::from("Line 2: Unknown action: X"))
+        );
+    }
+
+    #[test]
+    fn hexagonal_grids_turn_by_sixths() {
+        let actions = action::parse_actions::<Hex>("NE5\nR120\nF2\nL60\nSW3\nF1\n").unwrap();
+
+        assert_eq!(actions[1].to_string(), "R120");
+        assert!(action::parse_actions::<Hex>("R90").is_err());
+
+        let position = Orienteer::<Hex>::new().simulate(&actions, Mode::Normal);
+        assert_eq!((position.x, position.y, position.z), (3, -3, 0));
+
+        let position = Orienteer::<Hex>::new().simulate(&actions, Mode::Waypoint);
+        assert_eq!(position.x + position.y + position.z, 0);
+    }
+
+    #[test]
+    fn space_keeps_the_same_semantics_with_vertical_moves() {
+        let input = "F10\nN3\nF7\nR90\nF11\n";
+        let actions = action::parse_actions::<Space>(input).unwrap();
+
+        // Without any vertical moves, the results match the square grid
+        let position = Orienteer::<Space>::new().simulate(&actions, Mode::Waypoint);
+        assert_eq!(Space::distance(position), 286);
+
+        let actions = action::parse_actions::<Space>("U4\nF10\nD2\nR90\nF3\n").unwrap();
+
+        let position = Orienteer::<Space>::new().simulate(&actions, Mode::Normal);
+        assert_eq!((position.x, position.y, position.z), (10, -3, 2));
+
+        let position = Orienteer::<Space>::new().simulate(&actions, Mode::Waypoint);
+        assert_eq!(
+            (position.x, position.y, position.z),
+            (100 + 3, 10 - 30, 40 + 6)
+        );
+    }
 }
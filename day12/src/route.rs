@@ -0,0 +1,199 @@
+use std::fmt;
+
+use crate::action::Action;
+use crate::grid::Square;
+use crate::{Coordinate, Mode, Orienteer};
+
+/// The full path taken by the ship, starting from where it was before any actions.
+#[derive(Clone, Debug)]
+pub struct Route {
+    mode: Mode,
+    states: Vec<Orienteer<Square>>,
+}
+
+impl Route {
+    pub fn new(actions: &[Action<Square>], mode: Mode) -> Self {
+        let start = Orienteer::<Square>::new();
+
+        let mut states = vec![start];
+        states.extend(start.trace(actions, mode));
+
+        Self { mode, states }
+    }
+
+    /// Gets the absolute positions of the waypoint, which is stored relative to the ship.
+    fn waypoints(&self) -> impl Iterator<Item = Coordinate> + '_ {
+        self.states.iter().map(|s| Coordinate {
+            x: s.position.x + s.waypoint.x,
+            y: s.position.y + s.waypoint.y,
+        })
+    }
+
+    /// Gets the total Manhattan distance travelled by the ship.
+    pub fn distance(&self) -> i32 {
+        self.states
+            .windows(2)
+            .map(|w| {
+                (w[1].position.x - w[0].position.x).abs()
+                    + (w[1].position.y - w[0].position.y).abs()
+            })
+            .sum()
+    }
+
+    /// Gets the bottom left and top right corners of the box containing the ship's path.
+    pub fn bounding_box(&self) -> (Coordinate, Coordinate) {
+        bounds(self.states.iter().map(|s| s.position))
+    }
+
+    /// Gets the point on the path with the largest Manhattan distance from the origin.
+    pub fn furthest(&self) -> Coordinate {
+        self.states
+            .iter()
+            .map(|s| s.position)
+            .max_by_key(|p| p.x.abs() + p.y.abs())
+            .unwrap()
+    }
+
+    /// Renders the ship's path, and the waypoint's trail in waypoint mode, as an SVG image.
+    pub fn to_svg(&self) -> String {
+        let (mut min, mut max) = self.bounding_box();
+
+        if self.mode == Mode::Waypoint {
+            let (waypoint_min, waypoint_max) = bounds(self.waypoints());
+            min = bounds(vec![min, waypoint_min].into_iter()).0;
+            max = bounds(vec![max, waypoint_max].into_iter()).1;
+        }
+
+        // SVG has y increasing downwards, so north is flipped to be up
+        let points = |coordinates: &mut dyn Iterator<Item = Coordinate>| {
+            coordinates
+                .map(|c| format!("{},{}", c.x, -c.y))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let margin = ((max.x - min.x).max(max.y - min.y) / 20).max(1);
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+            min.x - margin,
+            -max.y - margin,
+            max.x - min.x + 2 * margin,
+            max.y - min.y + 2 * margin
+        );
+
+        if self.mode == Mode::Waypoint {
+            svg.push_str(&format!(
+                "  <polyline points=\"{}\" fill=\"none\" stroke=\"orange\" stroke-dasharray=\"4\" vector-effect=\"non-scaling-stroke\"/>\n",
+                points(&mut self.waypoints())
+            ));
+        }
+
+        svg.push_str(&format!(
+            "  <polyline points=\"{}\" fill=\"none\" stroke=\"navy\" vector-effect=\"non-scaling-stroke\"/>\n",
+            points(&mut self.states.iter().map(|s| s.position))
+        ));
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+fn bounds<I: Iterator<Item = Coordinate>>(coordinates: I) -> (Coordinate, Coordinate) {
+    coordinates.fold(
+        (
+            Coordinate {
+                x: i32::MAX,
+                y: i32::MAX,
+            },
+            Coordinate {
+                x: i32::MIN,
+                y: i32::MIN,
+            },
+        ),
+        |(min, max), c| {
+            (
+                Coordinate {
+                    x: min.x.min(c.x),
+                    y: min.y.min(c.y),
+                },
+                Coordinate {
+                    x: max.x.max(c.x),
+                    y: max.y.max(c.y),
+                },
+            )
+        },
+    )
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (min, max) = self.bounding_box();
+        let furthest = self.furthest();
+
+        writeln!(f, "Distance travelled: {}", self.distance())?;
+        writeln!(
+            f,
+            "Bounding box: ({}, {}) to ({}, {})",
+            min.x, min.y, max.x, max.y
+        )?;
+        write!(
+            f,
+            "Furthest point: ({}, {}) at distance {}",
+            furthest.x,
+            furthest.y,
+            furthest.x.abs() + furthest.y.abs()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::parse_actions;
+
+    fn example(mode: Mode) -> Route {
+        let actions = parse_actions::<Square>("F10\nN3\nF7\nR90\nF11\n").unwrap();
+        Route::new(&actions, mode)
+    }
+
+    #[test]
+    fn routes_report_their_statistics() {
+        let route = example(Mode::Normal);
+
+        assert_eq!(route.distance(), 31);
+        assert_eq!(
+            route.bounding_box(),
+            (Coordinate { x: 0, y: -8 }, Coordinate { x: 17, y: 3 })
+        );
+        assert_eq!(route.furthest(), Coordinate { x: 17, y: -8 });
+
+        let route = example(Mode::Waypoint);
+
+        assert_eq!(route.distance(), 362);
+        assert_eq!(
+            route.bounding_box(),
+            (Coordinate { x: 0, y: -72 }, Coordinate { x: 214, y: 38 })
+        );
+        assert_eq!(route.furthest(), Coordinate { x: 214, y: -72 });
+    }
+
+    #[test]
+    fn only_waypoint_routes_draw_the_waypoint_trail() {
+        let svg = example(Mode::Normal).to_svg();
+
+        assert!(svg.contains("viewBox=\"-1 -4 19 13\""));
+        assert!(svg.contains("points=\"0,0 10,0 10,-3 17,-3 17,-3 17,8\""));
+        assert!(!svg.contains("orange"));
+
+        let svg = example(Mode::Waypoint).to_svg();
+
+        // The view is widened to fit the waypoint, which strays outside the ship's path
+        assert!(svg.contains("viewBox=\"-10 -52 238 144\""));
+        assert!(svg.contains(
+            "<polyline points=\"10,-1 110,-11 110,-14 180,-42 174,-28 218,82\" fill=\"none\" stroke=\"orange\""
+        ));
+        assert!(svg.contains("points=\"0,0 100,-10 100,-10 170,-38 170,-38 214,72\""));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+use std::ops::{AddAssign, Mul};
+use std::str::FromStr;
+
+use crate::grid::Grid;
+
+/// A 3D grid where the ship can also move up and down, turning about the vertical axis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Space;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpaceHeading {
+    North,
+    East,
+    South,
+    West,
+    Up,
+    Down,
+}
+
+impl FromStr for SpaceHeading {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "N" => Ok(SpaceHeading::North),
+            "E" => Ok(SpaceHeading::East),
+            "S" => Ok(SpaceHeading::South),
+            "W" => Ok(SpaceHeading::West),
+            "U" => Ok(SpaceHeading::Up),
+            "D" => Ok(SpaceHeading::Down),
+            _ => Err(format!("Unknown heading: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for SpaceHeading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = match *self {
+            SpaceHeading::North => 'N',
+            SpaceHeading::East => 'E',
+            SpaceHeading::South => 'S',
+            SpaceHeading::West => 'W',
+            SpaceHeading::Up => 'U',
+            SpaceHeading::Down => 'D',
+        };
+
+        write!(f, "{}", c)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Point3D {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Mul<i32> for Point3D {
+    type Output = Self;
+
+    fn mul(self, value: i32) -> Self {
+        Self {
+            x: self.x * value,
+            y: self.y * value,
+            z: self.z * value,
+        }
+    }
+}
+
+impl AddAssign for Point3D {
+    fn add_assign(&mut self, other: Self) {
+        *self = Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        };
+    }
+}
+
+impl Grid for Space {
+    type Heading = SpaceHeading;
+    type Coordinate = Point3D;
+
+    const TURN: i32 = 90;
+    const HEADING: SpaceHeading = SpaceHeading::East;
+
+    fn waypoint() -> Point3D {
+        Point3D { x: 10, y: 1, z: 0 }
+    }
+
+    fn modifier(heading: SpaceHeading, value: i32) -> Point3D {
+        let (x, y, z) = match heading {
+            SpaceHeading::North => (0, 1, 0),
+            SpaceHeading::East => (1, 0, 0),
+            SpaceHeading::South => (0, -1, 0),
+            SpaceHeading::West => (-1, 0, 0),
+            SpaceHeading::Up => (0, 0, 1),
+            SpaceHeading::Down => (0, 0, -1),
+        };
+
+        Point3D { x, y, z } * value
+    }
+
+    fn turn(heading: SpaceHeading) -> SpaceHeading {
+        // Turning while facing straight up or down just spins the ship in place
+        match heading {
+            SpaceHeading::North => SpaceHeading::East,
+            SpaceHeading::East => SpaceHeading::South,
+            SpaceHeading::South => SpaceHeading::West,
+            SpaceHeading::West => SpaceHeading::North,
+            vertical => vertical,
+        }
+    }
+
+    fn turn_coordinate(coordinate: Point3D) -> Point3D {
+        Point3D {
+            x: coordinate.y,
+            y: -coordinate.x,
+            z: coordinate.z,
+        }
+    }
+
+    fn distance(coordinate: Point3D) -> i32 {
+        coordinate.x.abs() + coordinate.y.abs() + coordinate.z.abs()
+    }
+}
@@ -1,20 +1,124 @@
 use std::str::FromStr;
 
+mod schedule;
+
+use schedule::Schedule;
+
+fn parse_time(arg: Option<&String>, default: u64) -> u64 {
+    arg.map_or(default, |t| u64::from_str(t).unwrap())
+}
+
 fn main() {
     let input = std::fs::read_to_string("input.txt").unwrap();
     let lines: Vec<_> = input.trim().lines().collect();
 
-    let timestamp = u32::from_str(lines[0]).unwrap();
-    let bus_identifiers: Vec<_> = lines[1]
-        .split(',')
-        .filter_map(|t| u32::from_str(t).ok())
-        .collect();
+    let timestamp = u64::from_str(lines[0]).unwrap();
+    let schedule = Schedule::from_str(lines[1]).unwrap();
+
+    let args: Vec<_> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("departures") => {
+            let count = args.get(1).map_or(10, |n| usize::from_str(n).unwrap());
+            let time = parse_time(args.get(2), timestamp);
 
-    let (bus_id, wait_time) = bus_identifiers
-        .iter()
-        .map(|id| (id, id - (timestamp % id)))
+            for departure in schedule.departures(time).take(count) {
+                println!("{}: bus {}", departure.time, departure.bus);
+            }
+
+            return;
+        }
+        Some("waits") => {
+            for (bus, wait) in schedule.waits(parse_time(args.get(1), timestamp)) {
+                println!("Bus {}: {} minutes", bus, wait);
+            }
+
+            return;
+        }
+        Some("near") => {
+            let arg = |i: usize| u64::from_str(args.get(i).expect("Usage: near a b k [time]"));
+            let (a, b, k) = (arg(1).unwrap(), arg(2).unwrap(), arg(3).unwrap());
+            let time = parse_time(args.get(4), timestamp);
+
+            match schedule.first_within((a, b), k, time).unwrap() {
+                Some((x, y)) => println!("Bus {} departs at {} and bus {} at {}", a, x, b, y),
+                None => println!("Buses {} and {} never depart within {} minutes", a, b, k),
+            }
+
+            return;
+        }
+        _ => (),
+    }
+
+    let (bus_id, wait_time) = schedule
+        .waits(timestamp)
+        .into_iter()
         .min_by(|x, y| x.1.cmp(&y.1))
         .unwrap();
 
     println!("Part 1 Solution: {}", bus_id * wait_time);
+    println!(
+        "Part 2 Solution: {}",
+        schedule.earliest_alignment().unwrap()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn earliest(schedule: &str) -> Result<u128, String> {
+        Schedule::from_str(schedule)?.earliest_alignment()
+    }
+
+    #[test]
+    fn buses_line_up_at_their_offsets() {
+        assert_eq!(earliest("7,13,x,x,59,x,31,19"), Ok(1068781));
+        assert_eq!(earliest("17,x,13,19"), Ok(3417));
+        assert_eq!(earliest("67,7,59,61"), Ok(754018));
+        assert_eq!(earliest("1789,37,47,1889"), Ok(1202161486));
+    }
+
+    #[test]
+    fn buses_sharing_factors_are_rejected() {
+        assert!(earliest("6,x,4").is_err());
+        assert!(earliest("7,x,y").is_err());
+        assert!(earliest("7,0").is_err());
+    }
+
+    #[test]
+    fn departures_are_listed_in_order() {
+        let schedule = Schedule::from_str("7,13,x,x,59,x,31,19").unwrap();
+
+        let departures: Vec<_> = schedule
+            .departures(939)
+            .take(4)
+            .map(|d| (d.time, d.bus))
+            .collect();
+
+        assert_eq!(departures, vec![(944, 59), (945, 7), (949, 13), (950, 19)]);
+        assert_eq!(schedule.waits(945)[0], (7, 0));
+
+        let last = schedule.departures(u64::MAX - 10).last();
+        assert!(last.is_some_and(|d| d.time > u64::MAX - d.bus));
+    }
+
+    #[test]
+    fn buses_can_be_found_departing_close_together() {
+        let schedule = Schedule::from_str("7,13,x,x,59,x,31,19").unwrap();
+
+        assert_eq!(schedule.first_within((7, 13), 0, 0), Ok(Some((0, 0))));
+        assert_eq!(schedule.first_within((7, 13), 0, 1), Ok(Some((91, 91))));
+        assert_eq!(schedule.first_within((7, 13), 1, 1), Ok(Some((14, 13))));
+        assert_eq!(
+            schedule.first_within((59, 31), 2, 100),
+            Ok(Some((590, 589)))
+        );
+        assert!(schedule.first_within((7, 8), 1, 0).is_err());
+
+        // Common multiples always line up exactly, unless the timestamps run out first
+        let schedule = Schedule::from_str("4,6").unwrap();
+        assert_eq!(schedule.first_within((4, 6), 1, 1), Ok(Some((12, 12))));
+        assert_eq!(schedule.first_within((4, 6), 0, u64::MAX - 2), Ok(None));
+    }
 }
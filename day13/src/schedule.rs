@@ -0,0 +1,162 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::str::FromStr;
+
+/// The buses in service, each paired with its position in the list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    pub buses: Vec<(u64, u64)>,
+}
+
+/// A single bus leaving the station.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Departure {
+    pub time: u64,
+    pub bus: u64,
+}
+
+/// Every departure from the station in order, starting from a given time.
+#[derive(Clone, Debug)]
+pub struct Departures {
+    upcoming: BinaryHeap<Reverse<Departure>>,
+}
+
+impl Iterator for Departures {
+    type Item = Departure;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse(departure) = self.upcoming.pop()?;
+
+        // Buses stop running once their timestamps no longer fit
+        if let Some(time) = departure.time.checked_add(departure.bus) {
+            self.upcoming.push(Reverse(Departure { time, ..departure }));
+        }
+
+        Some(departure)
+    }
+}
+
+/// Gets how long it is from `time` until bus `id` next departs.
+pub fn wait(id: u64, time: u64) -> u64 {
+    (id - time % id) % id
+}
+
+impl Schedule {
+    pub fn identifiers(&self) -> impl Iterator<Item = u64> + '_ {
+        self.buses.iter().map(|(_, id)| *id)
+    }
+
+    fn check(&self, id: u64) -> Result<(), String> {
+        if self.identifiers().any(|i| i == id) {
+            Ok(())
+        } else {
+            Err(format!("Bus {} is not in service", id))
+        }
+    }
+
+    /// Gets how long each bus takes to depart after `time`, in schedule order.
+    pub fn waits(&self, time: u64) -> Vec<(u64, u64)> {
+        self.identifiers().map(|id| (id, wait(id, time))).collect()
+    }
+
+    /// Lists every departure at or after `time`, ordered by time and then bus.
+    pub fn departures(&self, time: u64) -> Departures {
+        let upcoming = self
+            .identifiers()
+            .filter_map(|bus| {
+                let time = time.checked_add(wait(bus, time))?;
+                Some(Reverse(Departure { time, bus }))
+            })
+            .collect();
+
+        Departures { upcoming }
+    }
+
+    /// Finds the first departures of buses `a` and `b` at or after `time` that are at most `k`
+    /// minutes apart.
+    pub fn first_within(
+        &self,
+        (a, b): (u64, u64),
+        k: u64,
+        time: u64,
+    ) -> Result<Option<(u64, u64)>, String> {
+        self.check(a)?;
+        self.check(b)?;
+
+        // The gaps between the two buses repeat once both have gone round their common multiple
+        let cycle = b / modular::gcd(a, b) + 1;
+
+        let mut departure_a = time.checked_add(wait(a, time));
+
+        for _ in 0..cycle {
+            let current = match departure_a {
+                Some(current) => current,
+                None => break,
+            };
+
+            let earliest = time.max(current.saturating_sub(k));
+
+            if let Some(departure_b) = earliest.checked_add(wait(b, earliest)) {
+                if departure_b <= current.saturating_add(k) {
+                    return Ok(Some((current, departure_b)));
+                }
+            }
+
+            departure_a = current.checked_add(a);
+        }
+
+        Ok(None)
+    }
+
+    /// Finds the earliest timestamp where each bus departs `offset` minutes after it.
+    pub fn earliest_alignment(&self) -> Result<u128, String> {
+        let congruences: Vec<_> = self
+            .buses
+            .iter()
+            .map(|&(offset, id)| {
+                let id = u128::from(id);
+                (modular::sub_mod(0, u128::from(offset), id), id)
+            })
+            .collect();
+
+        let mut product: u128 = 1;
+
+        for &(_, id) in &congruences {
+            if modular::gcd(product, id) != 1 {
+                return Err(format!(
+                    "Bus {} shares a factor with an earlier bus, so they may never line up",
+                    id
+                ));
+            }
+
+            product = product
+                .checked_mul(id)
+                .ok_or_else(|| String::from("Bus identifiers are too large to combine"))?;
+        }
+
+        modular::crt(&congruences)
+            .map(|(timestamp, _)| timestamp)
+            .map_err(|e| format!("Buses can never line up: {}", e))
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut buses = Vec::new();
+
+        for (offset, entry) in s.trim().split(',').enumerate() {
+            if entry == "x" {
+                continue;
+            }
+
+            match u64::from_str(entry) {
+                Ok(id) if id > 0 => buses.push((offset as u64, id)),
+                _ => return Err(format!("Invalid bus identifier: {}", entry)),
+            }
+        }
+
+        Ok(Self { buses })
+    }
+}
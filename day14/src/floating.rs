@@ -0,0 +1,109 @@
+/// A set of addresses, where any bit in `floating` can be either 0 or 1 and the rest are fixed
+/// to the bits of `base`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Pattern {
+    pub base: u64,
+    pub floating: u64,
+}
+
+impl Pattern {
+    pub fn new(base: u64, floating: u64) -> Self {
+        Self {
+            base: base & !floating,
+            floating,
+        }
+    }
+
+    /// Counts the addresses matched, which needs 65 bits when all 64 of them are floating.
+    pub fn len(&self) -> u128 {
+        1 << self.floating.count_ones()
+    }
+
+    /// Checks whether any address matches both patterns.
+    pub fn intersects(&self, other: &Pattern) -> bool {
+        let fixed = !self.floating & !other.floating;
+        (self.base ^ other.base) & fixed == 0
+    }
+
+    /// Splits the pattern into disjoint patterns that cover every address not in `other`.
+    pub fn subtract(&self, other: &Pattern) -> Vec<Pattern> {
+        if !self.intersects(other) {
+            return vec![*self];
+        }
+
+        let mut remaining = *self;
+        let mut pieces = Vec::new();
+
+        // Each bit we float but `other` fixes splits off the half that `other` cannot reach
+        let mut splitting = self.floating & !other.floating;
+
+        while splitting != 0 {
+            let bit = splitting & splitting.wrapping_neg();
+            splitting &= !bit;
+
+            remaining.floating &= !bit;
+
+            pieces.push(Pattern {
+                base: remaining.base | (!other.base & bit),
+                ..remaining
+            });
+
+            remaining.base |= other.base & bit;
+        }
+
+        pieces
+    }
+
+    pub fn addresses(&self) -> impl Iterator<Item = u64> + '_ {
+        let floating = self.floating;
+
+        // Counting through the subsets of the floating bits visits each address once
+        std::iter::successors(Some(0), move |subset: &u64| {
+            if *subset == floating {
+                None
+            } else {
+                Some((subset.wrapping_sub(floating)) & floating)
+            }
+        })
+        .map(move |subset| self.base | subset)
+    }
+}
+
+/// Memory written to by floating address patterns, without expanding them into addresses.
+#[derive(Clone, Debug, Default)]
+pub struct FloatingMemory {
+    regions: Vec<(Pattern, u64)>,
+}
+
+impl FloatingMemory {
+    /// Writes `value` to every address in `pattern`, keeping the regions disjoint by removing
+    /// the overwritten addresses from earlier writes.
+    pub fn write(&mut self, pattern: Pattern, value: u64) {
+        self.regions = self
+            .regions
+            .iter()
+            .flat_map(|(region, v)| region.subtract(&pattern).into_iter().map(move |p| (p, *v)))
+            .collect();
+
+        self.regions.push((pattern, value));
+    }
+
+    /// Counts the addresses written to, without expanding the patterns.
+    pub fn len(&self) -> u128 {
+        self.regions.iter().map(|(pattern, _)| pattern.len()).sum()
+    }
+
+    pub fn sum(&self) -> u128 {
+        self.regions
+            .iter()
+            .map(|(pattern, value)| pattern.len() * u128::from(*value))
+            .sum()
+    }
+
+    /// Lists every address and its value, which expands all the floating bits.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.regions
+            .iter()
+            .flat_map(|(pattern, value)| pattern.addresses().map(move |a| (a, *value)))
+    }
+}
@@ -0,0 +1,38 @@
+use std::collections::BTreeMap;
+
+/// Formats each address on its own line as `address: value`.
+pub fn to_lines(image: &BTreeMap<u64, u64>) -> String {
+    image
+        .iter()
+        .map(|(address, value)| format!("{}: {}\n", address, value))
+        .collect()
+}
+
+/// Formats the memory as a JSON object, using the addresses as keys.
+pub fn to_json(image: &BTreeMap<u64, u64>) -> String {
+    let entries: Vec<_> = image
+        .iter()
+        .map(|(address, value)| format!("  \"{}\": {}", address, value))
+        .collect();
+
+    if entries.is_empty() {
+        String::from("{}\n")
+    } else {
+        format!("{{\n{}\n}}\n", entries.join(",\n"))
+    }
+}
+
+/// Lists every address whose value differs between the images, with `-` for unwritten memory.
+pub fn diff(before: &BTreeMap<u64, u64>, after: &BTreeMap<u64, u64>) -> String {
+    let show = |value: Option<&u64>| value.map_or(String::from("-"), u64::to_string);
+
+    let mut addresses: Vec<_> = before.keys().chain(after.keys()).collect();
+    addresses.sort_unstable();
+    addresses.dedup();
+
+    addresses
+        .into_iter()
+        .filter(|a| before.get(a) != after.get(a))
+        .map(|a| format!("{}: {} -> {}\n", a, show(before.get(a)), show(after.get(a))))
+        .collect()
+}
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
 
 #[macro_use]
@@ -6,11 +6,20 @@ extern crate lazy_static;
 
 use regex::Regex;
 
+mod floating;
+mod image;
+mod mask;
+
+use floating::FloatingMemory;
+use mask::Mask;
+
 lazy_static! {
-    static ref MASK: Regex = Regex::new(r"^mask = ([01X]{36})$").unwrap();
     static ref ASSIGN: Regex = Regex::new(r"^mem\[(\d*)\] = (\d*)$").unwrap();
 }
 
+/// The most addresses an image will list, as floating writes can reach far too many to expand.
+const IMAGE_LIMIT: u128 = 1 << 20;
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 enum Version {
     One,
@@ -19,8 +28,9 @@ enum Version {
 
 #[derive(Debug, Default)]
 struct Initialiser {
-    mask: Vec<(usize, char)>,
+    mask: Mask,
     memory: HashMap<u64, u64>,
+    floating: FloatingMemory,
 }
 
 impl Initialiser {
@@ -33,47 +43,61 @@ impl Initialiser {
         }
     }
 
-    pub fn update_mask(&mut self, mask: Vec<(usize, char)>) {
+    pub fn update_mask(&mut self, mask: Mask) {
         self.mask = mask;
     }
 
     pub fn assign_value(&mut self, address: u64, value: u64, version: Version) {
         if version == Version::One {
-            self.memory.insert(address, apply_mask(&self.mask, value));
+            self.memory.insert(address, self.mask.apply_v1(value));
         } else {
-            let locations = apply_v2_mask(&self.mask, address);
-
-            for addr in locations {
-                self.memory.insert(addr, value);
-            }
+            self.floating.write(self.mask.apply_v2(address), value);
         }
     }
 
     pub fn reset(&mut self) {
         self.mask = Default::default();
         self.memory = Default::default();
+        self.floating = Default::default();
+    }
+
+    /// Sums every value in memory, whichever version wrote them.
+    pub fn sum(&self) -> u128 {
+        let fixed: u128 = self.memory.values().map(|v| u128::from(*v)).sum();
+        fixed + self.floating.sum()
+    }
+
+    /// Gets the value at every address that has been written to, in address order, refusing to
+    /// expand more than `IMAGE_LIMIT` addresses.
+    pub fn image(&self) -> Result<BTreeMap<u64, u64>, String> {
+        let written = self.memory.len() as u128 + self.floating.len();
+
+        if written > IMAGE_LIMIT {
+            return Err(format!(
+                "Memory has {} addresses written to, more than the {} that can be listed",
+                written, IMAGE_LIMIT
+            ));
+        }
+
+        Ok(self
+            .memory
+            .iter()
+            .map(|(a, v)| (*a, *v))
+            .chain(self.floating.iter())
+            .collect())
     }
 }
 
 #[derive(Clone, Debug)]
 enum Instruction {
-    Mask(Vec<(usize, char)>),
+    Mask(Mask),
     Assignment { address: u64, value: u64 },
 }
 
 impl From<&str> for Instruction {
     fn from(line: &str) -> Self {
-        if let Some(caps) = MASK.captures(line) {
-            let bitmask: Vec<_> = caps
-                .get(1)
-                .unwrap()
-                .as_str()
-                .chars()
-                .enumerate()
-                .map(|(i, x)| (35 - i, x))
-                .collect();
-
-            return Instruction::Mask(bitmask);
+        if line.starts_with("mask") {
+            return Instruction::Mask(Mask::from_str(line).unwrap());
         }
 
         let caps = ASSIGN.captures(line).unwrap();
@@ -85,39 +109,68 @@ impl From<&str> for Instruction {
     }
 }
 
-fn apply_mask(mask: &[(usize, char)], value: u64) -> u64 {
-    let mut result = value;
+fn run(instructions: &[Instruction], version: Version) -> Initialiser {
+    let mut initialiser = Initialiser::default();
 
-    for (addr, binary) in mask {
-        match binary {
-            '0' => result &= !(1 << addr),
-            '1' => result |= 1 << addr,
-            _ => (),
-        }
+    for instruction in instructions {
+        initialiser.handle(instruction.clone(), version);
     }
 
-    result
+    initialiser
 }
 
-fn apply_v2_mask(mask: &[(usize, char)], value: u64) -> HashSet<u64> {
-    let mut results = vec![value];
+/// Prints each write as it happens, along with the mask used and the addresses it reached.
+fn trace(instructions: &[Instruction], version: Version) {
+    const SHOWN: usize = 8;
 
-    for (addr, binary) in mask {
-        match binary {
-            '1' => {
-                results = results.iter().map(|x| x | 1 << addr).collect();
-            }
-            'X' => {
-                let with_zero: Vec<_> = results.iter().map(|x| x & !(1 << addr)).collect();
-                let with_one: Vec<_> = results.iter().map(|x| x | 1 << addr).collect();
-                results.extend(with_zero);
-                results.extend(with_one);
+    let mut initialiser = Initialiser::default();
+
+    for (i, instruction) in instructions.iter().enumerate() {
+        initialiser.handle(instruction.clone(), version);
+
+        let (address, value) = match *instruction {
+            Instruction::Mask(_) => continue,
+            Instruction::Assignment { address, value } => (address, value),
+        };
+
+        let mask = &initialiser.mask;
+
+        let written = match version {
+            Version::One => format!("{} = {}", address, mask.apply_v1(value)),
+            Version::Two => {
+                let pattern = mask.apply_v2(address);
+                let shown: Vec<_> = pattern
+                    .addresses()
+                    .take(SHOWN)
+                    .map(|a| a.to_string())
+                    .collect();
+
+                if pattern.len() > SHOWN as u128 {
+                    format!(
+                        "{}, ... ({} total) = {}",
+                        shown.join(", "),
+                        pattern.len(),
+                        value
+                    )
+                } else {
+                    format!("{} = {}", shown.join(", "), value)
+                }
             }
-            _ => (),
-        }
+        };
+
+        println!(
+            "{:>4}: {} | mem[{}] = {} -> {}",
+            i, mask, address, value, written
+        );
     }
+}
 
-    results.into_iter().collect()
+/// Prints a formatted memory image, or why it couldn't be listed.
+fn show(output: Result<String, String>) {
+    match output {
+        Ok(output) => print!("{}", output),
+        Err(e) => eprintln!("Error: {}", e),
+    }
 }
 
 fn main() {
@@ -126,66 +179,96 @@ fn main() {
 
     let instructions: Vec<_> = lines.iter().map(|x| Instruction::from(*x)).collect();
 
-    let mut initialiser = Initialiser::default();
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+
+    let version = match args.get(1) {
+        Some(&"v2") => Version::Two,
+        _ => Version::One,
+    };
+
+    match args.as_slice() {
+        ["dump", .., "--json"] => {
+            let image = run(&instructions, version).image();
+            return show(image.map(|i| image::to_json(&i)));
+        }
+        ["dump", ..] => {
+            let image = run(&instructions, version).image();
+            return show(image.map(|i| image::to_lines(&i)));
+        }
+        ["diff"] => {
+            let one = run(&instructions, Version::One).image();
+            let two = run(&instructions, Version::Two).image();
 
-    for instruction in instructions.clone() {
-        initialiser.handle(instruction, Version::One);
+            return show(one.and_then(|one| Ok(image::diff(&one, &two?))));
+        }
+        ["trace", ..] => return trace(&instructions, version),
+        _ => (),
     }
 
-    let solution: u64 = initialiser.memory.values().sum();
+    let mut initialiser = run(&instructions, Version::One);
+
+    let solution = initialiser.sum();
     println!("Part 1 Solution: {}", solution);
 
     initialiser.reset();
 
-    for instruction in instructions.clone() {
+    for instruction in instructions {
         initialiser.handle(instruction, Version::Two);
     }
 
-    let solution: u64 = initialiser.memory.values().sum();
+    let solution = initialiser.sum();
     println!("Part 2 Solution: {}", solution);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use floating::Pattern;
+    use std::collections::HashSet;
+
+    fn apply_v2_mask(mask: &str, address: u64) -> HashSet<u64> {
+        let mask = Mask::from_str(mask).unwrap();
+        mask.apply_v2(address).addresses().collect()
+    }
 
     #[test]
     fn masks_are_applied_correctly() {
-        let mask = vec![(0, '0'), (1, '1')];
+        let mask = Mask::from_str("mask = 10").unwrap();
         let value = 0b101;
 
         let expected = 0b110;
 
-        assert_eq!(apply_mask(&mask, value), expected);
+        assert_eq!(mask.apply_v1(value), expected);
     }
 
     #[test]
     fn v2_masks_are_applied_correctly() {
-        let mask = vec![(0, '0'), (1, '1'), (2, 'X')];
+        let mask = "mask = X10";
         let value = 0b001;
 
         let mut expected = HashSet::new();
         expected.insert(0b011);
         expected.insert(0b111);
 
-        assert_eq!(apply_v2_mask(&mask, value), expected);
+        assert_eq!(apply_v2_mask(mask, value), expected);
     }
 
     #[test]
     fn v2_masks_are_applied_correctly_for_longer_inputs() {
-        let mask = vec![(0, '0'), (1, '1'), (2, 'X'), (3, '1')];
+        let mask = "mask = 1X10";
         let value = 0b001;
 
         let mut expected = HashSet::new();
         expected.insert(0b1011);
         expected.insert(0b1111);
 
-        assert_eq!(apply_v2_mask(&mask, value), expected);
+        assert_eq!(apply_v2_mask(mask, value), expected);
     }
 
     #[test]
     fn v2_masks_are_applied_correctly_for_even_longer_inputs() {
-        let mask = vec![(0, '0'), (1, '1'), (2, 'X'), (3, '1'), (4, 'X')];
+        let mask = "mask = X1X10";
         let value = 0b001;
 
         let mut expected = HashSet::new();
@@ -194,6 +277,155 @@ mod tests {
         expected.insert(0b11011);
         expected.insert(0b11111);
 
-        assert_eq!(apply_v2_mask(&mask, value), expected);
+        assert_eq!(apply_v2_mask(mask, value), expected);
+    }
+
+    #[test]
+    fn v2_patterns_match_the_listed_addresses() {
+        let mask = Mask::from_str("mask = X1X10").unwrap();
+        let pattern = mask.apply_v2(0b001);
+
+        assert_eq!(pattern.len(), 4);
+        assert!(pattern.addresses().all(|a| a & 0b01011 == 0b01011));
+    }
+
+    #[test]
+    fn overlapping_v2_writes_are_only_counted_once() {
+        let program = [
+            "mask = 000000000000000000000000000000X1001X",
+            "mem[42] = 100",
+            "mask = 00000000000000000000000000000000X0XX",
+            "mem[26] = 1",
+        ];
+
+        let mut initialiser = Initialiser::default();
+
+        for line in &program {
+            initialiser.handle(Instruction::from(*line), Version::Two);
+        }
+
+        assert_eq!(initialiser.sum(), 208);
+
+        // Enumerating every address written to gives the same result
+        let mut memory = HashMap::new();
+        let mut mask = Mask::default();
+
+        for line in &program {
+            match Instruction::from(*line) {
+                Instruction::Mask(m) => mask = m,
+                Instruction::Assignment { address, value } => {
+                    for address in mask.apply_v2(address).addresses() {
+                        memory.insert(address, value);
+                    }
+                }
+            }
+        }
+
+        assert_eq!(memory.values().sum::<u64>(), 208);
+    }
+
+    #[test]
+    fn subtracting_patterns_leaves_disjoint_pieces() {
+        let a = Pattern::new(0, 0b1111);
+        let b = Pattern::new(0b0101, 0b1010);
+
+        let pieces = a.subtract(&b);
+
+        let remaining: HashSet<_> = pieces.iter().flat_map(|p| p.addresses()).collect();
+        let expected: HashSet<_> = a.addresses().filter(|x| x & 0b0101 != 0b0101).collect();
+
+        assert_eq!(remaining, expected);
+        assert_eq!(pieces.iter().map(Pattern::len).sum::<u128>(), 12);
+        assert_eq!(
+            Pattern::new(0b1, 0b10)
+                .subtract(&Pattern::new(0, 0b100))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn masks_can_be_round_tripped() {
+        let line = "mask = 000000000000000000000000000000X1001X";
+        let mask = Mask::from_str(line).unwrap();
+
+        assert_eq!(mask.width, 36);
+        assert_eq!(mask.ones, 0b10010);
+        assert_eq!(mask.floating, 0b100001);
+        assert_eq!(mask.to_string(), line);
+
+        for line in &[
+            "mask = X",
+            "mask = 1",
+            &format!("mask = {}", "X01".repeat(21)),
+        ] {
+            assert_eq!(Mask::from_str(line).unwrap().to_string(), *line);
+        }
+
+        // Every address can float when the mask is as wide as the addresses
+        let line = format!("mask = {}", "X".repeat(64));
+        let mask = Mask::from_str(&line).unwrap();
+
+        assert_eq!(mask.to_string(), line);
+        assert_eq!(mask.apply_v2(0).len(), 1 << 64);
+
+        let mut initialiser = Initialiser::default();
+        initialiser.handle(Instruction::Mask(mask), Version::Two);
+        initialiser.handle(Instruction::from("mem[0] = 1"), Version::Two);
+
+        assert_eq!(initialiser.sum(), 1 << 64);
+
+        assert!(Mask::from_str("mask = ").is_err());
+        assert!(Mask::from_str("mask = 10Y").is_err());
+        assert!(Mask::from_str(&format!("mask = {}", "0".repeat(65))).is_err());
+    }
+
+    #[test]
+    fn memory_images_can_be_dumped_and_diffed() {
+        let instructions: Vec<_> = ["mask = 0X1", "mem[4] = 2", "mem[0] = 0"]
+            .iter()
+            .map(|line| Instruction::from(*line))
+            .collect();
+
+        let one = run(&instructions, Version::One).image().unwrap();
+        let two = run(&instructions, Version::Two).image().unwrap();
+
+        assert_eq!(image::to_lines(&one), "0: 1\n4: 3\n");
+        assert_eq!(
+            image::to_json(&two),
+            "{\n  \"1\": 0,\n  \"3\": 0,\n  \"5\": 2,\n  \"7\": 2\n}\n"
+        );
+        assert_eq!(image::to_json(&BTreeMap::new()), "{}\n");
+
+        let expected = "0: 1 -> -\n1: - -> 0\n3: - -> 0\n4: 3 -> -\n5: - -> 2\n7: - -> 2\n";
+        assert_eq!(image::diff(&one, &two), expected);
+    }
+
+    #[test]
+    fn images_too_large_to_list_are_refused() {
+        let wide = format!("mask = {}{}", "0".repeat(16), "X".repeat(20));
+        let wider = format!("mask = {}{}", "0".repeat(15), "X".repeat(21));
+
+        for (mask, allowed) in &[(wide, true), (wider, false)] {
+            let instructions = vec![
+                Instruction::from(mask.as_str()),
+                Instruction::from("mem[0] = 1"),
+            ];
+            let initialiser = run(&instructions, Version::Two);
+
+            assert_eq!(initialiser.image().is_ok(), *allowed);
+        }
+
+        let instructions = vec![
+            Instruction::from(format!("mask = {}", "X".repeat(64)).as_str()),
+            Instruction::from("mem[0] = 1"),
+        ];
+
+        assert_eq!(
+            run(&instructions, Version::Two).image(),
+            Err(String::from(
+                "Memory has 18446744073709551616 addresses written to, more than the 1048576 that can be listed"
+            ))
+        );
     }
 }
@@ -0,0 +1,76 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::floating::Pattern;
+
+/// A bitmask from a `mask = ...` line, where the leftmost character is the most significant bit.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Mask {
+    pub ones: u64,
+    pub zeros: u64,
+    pub floating: u64,
+    pub width: u32,
+}
+
+impl Mask {
+    /// Overwrites the bits of `value` that the mask sets to 0 or 1.
+    pub fn apply_v1(&self, value: u64) -> u64 {
+        (value & !self.zeros) | self.ones
+    }
+
+    /// Gets the addresses written to by the mask, without listing them all.
+    pub fn apply_v2(&self, address: u64) -> Pattern {
+        Pattern::new(address | self.ones, self.floating)
+    }
+}
+
+impl FromStr for Mask {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bits = s
+            .strip_prefix("mask = ")
+            .ok_or_else(|| format!("Expected a mask: {}", s))?;
+
+        if bits.is_empty() || bits.len() > 64 {
+            return Err(format!(
+                "Masks must be 1 to 64 bits wide, got {}",
+                bits.len()
+            ));
+        }
+
+        let mut mask = Mask {
+            width: bits.len() as u32,
+            ..Default::default()
+        };
+
+        for (i, c) in bits.chars().rev().enumerate() {
+            match c {
+                '0' => mask.zeros |= 1 << i,
+                '1' => mask.ones |= 1 << i,
+                'X' => mask.floating |= 1 << i,
+                _ => return Err(format!("Invalid character in mask: {}", c)),
+            }
+        }
+
+        Ok(mask)
+    }
+}
+
+impl fmt::Display for Mask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "mask = ")?;
+
+        for i in (0..self.width).rev() {
+            let c = match (self.ones >> i & 1, self.floating >> i & 1) {
+                (1, _) => '1',
+                (_, 1) => 'X',
+                _ => '0',
+            };
+
+            write!(f, "{}", c)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::Instruction;
+
+/// Where control goes after the last instruction of a block.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Target {
+    Block(usize),
+    Exit,
+    Outside,
+}
+
+/// What happens when the program is executed from the first instruction.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Ending {
+    Terminates,
+    Loops,
+    Escapes,
+}
+
+/// A run of instructions that always execute together, covering `start..end`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Block {
+    pub start: usize,
+    pub end: usize,
+    pub target: Target,
+}
+
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub blocks: Vec<Block>,
+    /// Instructions that are never executed when starting from the first one.
+    pub unreachable: Vec<usize>,
+    /// Jumps whose target is neither an instruction nor the end of the program.
+    pub escaping: Vec<usize>,
+    /// Sets of instructions that loop forever once any of them is executed.
+    pub loops: Vec<Vec<usize>>,
+    pub ending: Ending,
+}
+
+/// Gets the successor of an instruction, or `None` if it leaves the program.
+fn successor(instructions: &[Instruction], index: usize) -> Option<usize> {
+    instructions[index]
+        .successor(index)
+        .filter(|next| *next <= instructions.len())
+}
+
+fn find_blocks(instructions: &[Instruction]) -> Vec<Block> {
+    let end = instructions.len();
+    let mut leaders = BTreeSet::new();
+    leaders.insert(0);
+
+    // Any jump target starts a block, as does whatever follows a jump
+    for (i, instruction) in instructions.iter().enumerate() {
+        if let Instruction::Jump(_) = instruction {
+            if let Some(next) = successor(instructions, i) {
+                leaders.insert(next);
+            }
+
+            leaders.insert(i + 1);
+        }
+    }
+
+    let leaders: Vec<_> = leaders.into_iter().filter(|l| *l < end).collect();
+
+    leaders
+        .iter()
+        .enumerate()
+        .map(|(b, &start)| {
+            let end = leaders.get(b + 1).copied().unwrap_or(end);
+
+            let target = match successor(instructions, end - 1) {
+                Some(next) if next == instructions.len() => Target::Exit,
+                Some(next) => Target::Block(leaders.binary_search(&next).unwrap()),
+                None => Target::Outside,
+            };
+
+            Block { start, end, target }
+        })
+        .collect()
+}
+
+/// Finds every cycle in the control-flow graph, which is easy as each instruction has at most
+/// one successor.
+fn find_loops(instructions: &[Instruction]) -> Vec<Vec<usize>> {
+    const UNSEEN: u8 = 0;
+    const ON_PATH: u8 = 1;
+    const DONE: u8 = 2;
+
+    let mut state = vec![UNSEEN; instructions.len()];
+    let mut loops = Vec::new();
+
+    for start in 0..instructions.len() {
+        let mut path = Vec::new();
+        let mut current = Some(start);
+
+        while let Some(i) = current.filter(|i| *i < instructions.len()) {
+            match state[i] {
+                UNSEEN => {
+                    state[i] = ON_PATH;
+                    path.push(i);
+                    current = successor(instructions, i);
+                }
+                ON_PATH => {
+                    let position = path.iter().position(|p| *p == i).unwrap();
+                    let mut cycle = path[position..].to_vec();
+                    cycle.sort_unstable();
+                    loops.push(cycle);
+                    break;
+                }
+                _ => break,
+            }
+        }
+
+        for i in path {
+            state[i] = DONE;
+        }
+    }
+
+    loops
+}
+
+pub fn analyse(instructions: &[Instruction]) -> Report {
+    let mut reachable = vec![false; instructions.len()];
+    let mut current = Some(0);
+
+    // Execution is deterministic, so the reachable instructions are a single path
+    while let Some(i) = current.filter(|i| *i < instructions.len() && !reachable[*i]) {
+        reachable[i] = true;
+        current = successor(instructions, i);
+    }
+
+    let ending = match current {
+        Some(i) if i == instructions.len() => Ending::Terminates,
+        Some(_) => Ending::Loops,
+        None => Ending::Escapes,
+    };
+
+    let unreachable = (0..instructions.len()).filter(|i| !reachable[*i]).collect();
+
+    let escaping = (0..instructions.len())
+        .filter(|i| successor(instructions, *i).is_none())
+        .collect();
+
+    Report {
+        blocks: find_blocks(instructions),
+        unreachable,
+        escaping,
+        loops: find_loops(instructions),
+        ending,
+    }
+}
+
+impl Report {
+    /// Renders the control-flow graph of basic blocks in the DOT language.
+    pub fn to_dot(&self, instructions: &[Instruction]) -> String {
+        let mut dot = String::from("digraph boot {\n    node [shape=box, fontname=monospace];\n");
+        let looping: BTreeSet<_> = self.loops.iter().flatten().collect();
+
+        for (b, block) in self.blocks.iter().enumerate() {
+            let label: String = (block.start..block.end)
+                .map(|i| format!("{}: {}\\l", i, instructions[i]))
+                .collect();
+
+            let mut attributes = format!("label=\"{}\"", label);
+
+            if self.unreachable.contains(&block.start) {
+                attributes.push_str(", style=dashed");
+            }
+
+            if looping.contains(&block.start) {
+                attributes.push_str(", color=red");
+            }
+
+            dot.push_str(&format!("    b{} [{}];\n", b, attributes));
+
+            let target = match block.target {
+                Target::Block(t) => format!("b{}", t),
+                Target::Exit => String::from("exit"),
+                Target::Outside => String::from("outside"),
+            };
+
+            dot.push_str(&format!("    b{} -> {};\n", b, target));
+        }
+
+        dot.push_str("    exit [shape=doublecircle];\n");
+
+        if !self.escaping.is_empty() {
+            dot.push_str("    outside [shape=octagon, color=red];\n");
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Basic blocks: {}", self.blocks.len())?;
+
+        for (b, block) in self.blocks.iter().enumerate() {
+            let target = match block.target {
+                Target::Block(t) => format!("block {}", t),
+                Target::Exit => String::from("exit"),
+                Target::Outside => String::from("outside the program"),
+            };
+
+            writeln!(
+                f,
+                "  block {}: {}..{} -> {}",
+                b, block.start, block.end, target
+            )?;
+        }
+
+        writeln!(f, "Unreachable instructions: {:?}", self.unreachable)?;
+        writeln!(f, "Jumps leaving the program: {:?}", self.escaping)?;
+
+        for cycle in &self.loops {
+            writeln!(f, "Infinite loop through instructions: {:?}", cycle)?;
+        }
+
+        match self.ending {
+            Ending::Terminates => write!(f, "Execution from the start terminates"),
+            Ending::Loops => write!(f, "Execution from the start loops forever"),
+            Ending::Escapes => write!(f, "Execution from the start jumps outside the program"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+
+    const EXAMPLE: &[&str] = &[
+        "nop +0", "acc +1", "jmp +4", "acc +3", "jmp -3", "acc -99", "acc +1", "jmp -4", "acc +6",
+    ];
+
+    fn block(start: usize, end: usize, target: Target) -> Block {
+        Block { start, end, target }
+    }
+
+    #[test]
+    fn the_example_is_split_into_basic_blocks() {
+        let report = analyse(&parse_input(EXAMPLE));
+
+        let expected = vec![
+            block(0, 1, Target::Block(1)),
+            block(1, 3, Target::Block(4)),
+            block(3, 5, Target::Block(1)),
+            block(5, 6, Target::Block(4)),
+            block(6, 8, Target::Block(2)),
+            block(8, 9, Target::Exit),
+        ];
+
+        assert_eq!(report.blocks, expected);
+        assert_eq!(report.unreachable, vec![5, 8]);
+        assert!(report.escaping.is_empty());
+        assert_eq!(report.loops, vec![vec![1, 2, 3, 4, 6, 7]]);
+        assert_eq!(report.ending, Ending::Loops);
+    }
+
+    #[test]
+    fn jumps_leaving_the_program_are_reported() {
+        let report = analyse(&parse_input(&["nop +0", "jmp -5", "jmp +7"]));
+
+        let expected = vec![block(0, 2, Target::Outside), block(2, 3, Target::Outside)];
+
+        assert_eq!(report.blocks, expected);
+        assert_eq!(report.unreachable, vec![2]);
+        assert_eq!(report.escaping, vec![1, 2]);
+        assert!(report.loops.is_empty());
+        assert_eq!(report.ending, Ending::Escapes);
+    }
+
+    #[test]
+    fn programs_without_jumps_terminate() {
+        let report = analyse(&parse_input(&["acc +1", "nop -1"]));
+
+        assert_eq!(report.blocks, vec![block(0, 2, Target::Exit)]);
+        assert!(report.unreachable.is_empty());
+        assert_eq!(report.ending, Ending::Terminates);
+    }
+
+    #[test]
+    fn empty_programs_terminate_immediately() {
+        let report = analyse(&[]);
+
+        assert!(report.blocks.is_empty());
+        assert!(report.unreachable.is_empty());
+        assert!(report.escaping.is_empty());
+        assert!(report.loops.is_empty());
+        assert_eq!(report.ending, Ending::Terminates);
+
+        let expected = "digraph boot {\n    node [shape=box, fontname=monospace];\n    exit [shape=doublecircle];\n}\n";
+        assert_eq!(report.to_dot(&[]), expected);
+    }
+
+    #[test]
+    fn jumping_to_itself_is_a_loop() {
+        let report = analyse(&parse_input(&["jmp +0", "acc +1"]));
+
+        let expected = vec![block(0, 1, Target::Block(0)), block(1, 2, Target::Exit)];
+
+        assert_eq!(report.blocks, expected);
+        assert_eq!(report.unreachable, vec![1]);
+        assert!(report.escaping.is_empty());
+        assert_eq!(report.loops, vec![vec![0]]);
+        assert_eq!(report.ending, Ending::Loops);
+    }
+
+    #[test]
+    fn blocks_are_rendered_as_dot() {
+        let instructions = parse_input(EXAMPLE);
+        let dot = analyse(&instructions).to_dot(&instructions);
+
+        assert!(dot.contains("    b1 [label=\"1: acc +1\\l2: jmp +4\\l\", color=red];\n"));
+        assert!(dot.contains("    b3 [label=\"5: acc -99\\l\", style=dashed];\n    b3 -> b4;\n"));
+        assert!(dot.contains("    b5 -> exit;\n"));
+        assert!(!dot.contains("outside"));
+
+        let instructions = parse_input(&["jmp +2"]);
+        let dot = analyse(&instructions).to_dot(&instructions);
+
+        assert!(dot.contains("    b0 -> outside;\n"));
+        assert!(dot.contains("    outside [shape=octagon, color=red];\n"));
+    }
+}
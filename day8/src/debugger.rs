@@ -0,0 +1,455 @@
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use crate::Machine;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Breakpoint {
+    ProgramCounter(usize),
+    Accumulator(i32),
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Breakpoint::ProgramCounter(pc) => write!(f, "pc == {}", pc),
+            Breakpoint::Accumulator(acc) => write!(f, "acc == {}", acc),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Term {
+    Accumulator,
+    ProgramCounter,
+    Constant(i32),
+}
+
+impl Term {
+    fn evaluate(&self, machine: &Machine) -> i32 {
+        match *self {
+            Term::Accumulator => machine.accumulator,
+            Term::ProgramCounter => machine.program_counter as i32,
+            Term::Constant(v) => v,
+        }
+    }
+}
+
+impl FromStr for Term {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "acc" => Ok(Term::Accumulator),
+            "pc" => Ok(Term::ProgramCounter),
+            _ => i32::from_str(s)
+                .map(Term::Constant)
+                .map_err(|_| format!("Unknown term: {}", s)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Operator {
+    /// Applies the operator, returning `None` if the arithmetic overflows.
+    fn apply(self, left: i32, right: i32) -> Option<i32> {
+        match self {
+            Operator::Add => left.checked_add(right),
+            Operator::Subtract => left.checked_sub(right),
+            Operator::Equal => Some((left == right) as i32),
+            Operator::NotEqual => Some((left != right) as i32),
+            Operator::Less => Some((left < right) as i32),
+            Operator::LessOrEqual => Some((left <= right) as i32),
+            Operator::Greater => Some((left > right) as i32),
+            Operator::GreaterOrEqual => Some((left >= right) as i32),
+        }
+    }
+}
+
+impl FromStr for Operator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "+" => Ok(Operator::Add),
+            "-" => Ok(Operator::Subtract),
+            "==" => Ok(Operator::Equal),
+            "!=" => Ok(Operator::NotEqual),
+            "<" => Ok(Operator::Less),
+            "<=" => Ok(Operator::LessOrEqual),
+            ">" => Ok(Operator::Greater),
+            ">=" => Ok(Operator::GreaterOrEqual),
+            _ => Err(format!("Unknown operator: {}", s)),
+        }
+    }
+}
+
+/// A watch expression such as `acc`, `pc + 1` or `acc >= 100`.
+#[derive(Clone, Debug)]
+struct Expression {
+    source: String,
+    left: Term,
+    right: Option<(Operator, Term)>,
+}
+
+impl Expression {
+    /// Evaluates the expression, returning `None` if it overflows.
+    fn evaluate(&self, machine: &Machine) -> Option<i32> {
+        let left = self.left.evaluate(machine);
+
+        match self.right {
+            Some((operator, term)) => operator.apply(left, term.evaluate(machine)),
+            None => Some(left),
+        }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split_whitespace().collect();
+
+        let (left, right) = match parts.as_slice() {
+            [left] => (Term::from_str(left)?, None),
+            [left, operator, right] => {
+                let operator = Operator::from_str(operator)?;
+                let right = Term::from_str(right)?;
+                (Term::from_str(left)?, Some((operator, right)))
+            }
+            _ => return Err(format!("Invalid expression: {}", s)),
+        };
+
+        Ok(Self {
+            source: parts.join(" "),
+            left,
+            right,
+        })
+    }
+}
+
+/// Formats the value of a watch expression, which may have overflowed.
+fn show(value: Option<i32>) -> String {
+    value.map_or(String::from("overflow"), |v| v.to_string())
+}
+
+/// Why the debugger stopped running the machine.
+enum Stop {
+    Stepped,
+    Breakpoint(Breakpoint),
+    Watch(usize, Option<i32>, Option<i32>),
+    Loop,
+    Escaped,
+    Overflowed,
+    Terminated,
+}
+
+/// An interactive debugger that drives a [`Machine`] from commands read line by line.
+pub struct Debugger {
+    machine: Machine,
+    breakpoints: Vec<Breakpoint>,
+    watches: Vec<(Expression, Option<i32>)>,
+    trace: bool,
+}
+
+impl Debugger {
+    pub fn new(machine: Machine) -> Self {
+        Self {
+            machine,
+            breakpoints: Vec::new(),
+            watches: Vec::new(),
+            trace: false,
+        }
+    }
+
+    /// Reads commands from `input` until it is exhausted or `quit` is entered.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            let words: Vec<_> = line.split_whitespace().collect();
+
+            match words.as_slice() {
+                [] => continue,
+                ["quit"] | ["q"] => break,
+                _ => {
+                    if let Err(e) = self.execute(&words, &mut output)? {
+                        writeln!(output, "error: {}", e)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute<W: Write>(
+        &mut self,
+        words: &[&str],
+        output: &mut W,
+    ) -> io::Result<Result<(), String>> {
+        match words {
+            ["step"] | ["s"] => self.step(1, output)?,
+            ["step", count] | ["s", count] => match usize::from_str(count) {
+                Ok(count) => self.step(count, output)?,
+                Err(_) => return Ok(Err(format!("Invalid step count: {}", count))),
+            },
+            ["continue"] | ["c"] => {
+                let stop = self.resume(usize::MAX, output)?;
+                self.report(stop, output)?;
+            }
+            ["break", "pc", value] => match usize::from_str(value) {
+                Ok(pc) => self.add_breakpoint(Breakpoint::ProgramCounter(pc), output)?,
+                Err(_) => return Ok(Err(format!("Invalid program counter: {}", value))),
+            },
+            ["break", "acc", value] => match i32::from_str(value) {
+                Ok(acc) => self.add_breakpoint(Breakpoint::Accumulator(acc), output)?,
+                Err(_) => return Ok(Err(format!("Invalid accumulator: {}", value))),
+            },
+            ["delete"] => self.breakpoints.clear(),
+            ["watch", expression @ ..] => match Expression::from_str(&expression.join(" ")) {
+                Ok(expression) => {
+                    let value = expression.evaluate(&self.machine);
+                    writeln!(
+                        output,
+                        "watch {}: {} = {}",
+                        self.watches.len(),
+                        expression.source,
+                        show(value)
+                    )?;
+                    self.watches.push((expression, value));
+                }
+                Err(e) => return Ok(Err(e)),
+            },
+            ["trace", "on"] => self.trace = true,
+            ["trace", "off"] => self.trace = false,
+            ["print"] | ["p"] => self.print(output)?,
+            ["reset"] => {
+                self.machine.reset();
+                self.refresh_watches();
+                self.print(output)?;
+            }
+            _ => return Ok(Err(format!("Unknown command: {}", words.join(" ")))),
+        }
+
+        Ok(Ok(()))
+    }
+
+    fn add_breakpoint<W: Write>(
+        &mut self,
+        breakpoint: Breakpoint,
+        output: &mut W,
+    ) -> io::Result<()> {
+        writeln!(
+            output,
+            "breakpoint {}: {}",
+            self.breakpoints.len(),
+            breakpoint
+        )?;
+        self.breakpoints.push(breakpoint);
+
+        Ok(())
+    }
+
+    fn step<W: Write>(&mut self, count: usize, output: &mut W) -> io::Result<()> {
+        let stop = self.resume(count, output)?;
+        self.report(stop, output)
+    }
+
+    /// Executes up to `limit` instructions, stopping early at breakpoints, watches, loops, jumps
+    /// outside the program, accumulator overflows or termination.
+    fn resume<W: Write>(&mut self, limit: usize, output: &mut W) -> io::Result<Stop> {
+        for executed in 0..limit {
+            if self.machine.is_terminated() {
+                return Ok(Stop::Terminated);
+            }
+
+            if self.machine.is_escaped() {
+                return Ok(Stop::Escaped);
+            }
+
+            if self.machine.is_overflowed() {
+                return Ok(Stop::Overflowed);
+            }
+
+            if self.machine.has_looped() {
+                return Ok(Stop::Loop);
+            }
+
+            let pc = self.machine.program_counter;
+            let acc = self.machine.accumulator;
+
+            // Don't immediately stop on the breakpoint we are resuming from
+            if executed > 0 && self.breakpoints.contains(&Breakpoint::ProgramCounter(pc)) {
+                return Ok(Stop::Breakpoint(Breakpoint::ProgramCounter(pc)));
+            }
+
+            let instruction = match self.machine.current() {
+                Some(instruction) => instruction.to_string(),
+                None => return Ok(Stop::Escaped),
+            };
+
+            self.machine.step();
+
+            if self.trace {
+                writeln!(
+                    output,
+                    "{:>5}: {:<10} acc = {}",
+                    pc, instruction, self.machine.accumulator
+                )?;
+            }
+
+            if self.machine.is_escaped() {
+                return Ok(Stop::Escaped);
+            }
+
+            if self.machine.is_overflowed() {
+                return Ok(Stop::Overflowed);
+            }
+
+            // Accumulator breakpoints trigger when the value is reached, not while it stays there
+            let reached = Breakpoint::Accumulator(self.machine.accumulator);
+
+            if acc != self.machine.accumulator && self.breakpoints.contains(&reached) {
+                return Ok(Stop::Breakpoint(reached));
+            }
+
+            for (i, (expression, value)) in self.watches.iter_mut().enumerate() {
+                let updated = expression.evaluate(&self.machine);
+
+                if updated != *value {
+                    let previous = std::mem::replace(value, updated);
+                    return Ok(Stop::Watch(i, previous, updated));
+                }
+            }
+        }
+
+        Ok(Stop::Stepped)
+    }
+
+    fn report<W: Write>(&self, stop: Stop, output: &mut W) -> io::Result<()> {
+        match stop {
+            Stop::Stepped => (),
+            Stop::Breakpoint(b) => writeln!(output, "hit breakpoint: {}", b)?,
+            Stop::Watch(i, previous, updated) => writeln!(
+                output,
+                "watch {}: {} changed from {} to {}",
+                i,
+                self.watches[i].0.source,
+                show(previous),
+                show(updated)
+            )?,
+            Stop::Loop => writeln!(output, "loop detected")?,
+            Stop::Escaped => writeln!(output, "jumped outside the program")?,
+            Stop::Overflowed => writeln!(output, "accumulator overflowed")?,
+            Stop::Terminated => writeln!(output, "program terminated")?,
+        }
+
+        self.print(output)
+    }
+
+    fn refresh_watches(&mut self) {
+        for (expression, value) in &mut self.watches {
+            *value = expression.evaluate(&self.machine);
+        }
+    }
+
+    fn print<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        let next = match self.machine.current() {
+            Some(instruction) => instruction.to_string(),
+            None => String::from("<end>"),
+        };
+
+        writeln!(
+            output,
+            "pc = {}, acc = {}, next = {}",
+            self.machine.program_counter, self.machine.accumulator, next
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+
+    const PROGRAM: &[&str] = &[
+        "nop +0", "acc +1", "jmp +4", "acc +3", "jmp -3", "acc -99", "acc +1", "jmp -4", "acc +6",
+    ];
+
+    fn debug(script: &str) -> String {
+        debug_program(PROGRAM, script)
+    }
+
+    fn debug_program(program: &[&str], script: &str) -> String {
+        let machine = Machine::new(parse_input(program));
+        let mut debugger = Debugger::new(machine);
+        let mut output = Vec::new();
+
+        debugger.run(script.as_bytes(), &mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn steps_can_be_traced() {
+        let output = debug("trace on\nstep 3");
+
+        let expected = "    0: nop +0     acc = 0\n    1: acc +1     acc = 1\n    2: jmp +4     acc = 1\npc = 6, acc = 1, next = acc +1\n";
+
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn continue_stops_at_breakpoints_and_loops() {
+        let output = debug("break pc 4\nbreak acc 2\nc\nc\nc\nq\nc");
+
+        let expected = "breakpoint 0: pc == 4\nbreakpoint 1: acc == 2\nhit breakpoint: acc == 2\npc = 7, acc = 2, next = jmp -4\nhit breakpoint: pc == 4\npc = 4, acc = 5, next = jmp -3\nloop detected\npc = 1, acc = 5, next = acc +1\n";
+
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn watches_interrupt_execution_when_they_change() {
+        let output = debug("watch acc > 1\nc\nfoo");
+
+        let expected = "watch 0: acc > 1 = 0\nwatch 0: acc > 1 changed from 0 to 1\npc = 7, acc = 2, next = jmp -4\nerror: Unknown command: foo\n";
+
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn jumps_outside_the_program_stop_execution() {
+        let output = debug_program(&["jmp +5", "acc +1"], "s\ns\nc");
+
+        let expected = "jumped outside the program\npc = 0, acc = 0, next = jmp +5\njumped outside the program\npc = 0, acc = 0, next = jmp +5\njumped outside the program\npc = 0, acc = 0, next = jmp +5\n";
+        assert_eq!(output, expected);
+
+        let output = debug_program(&["acc +1", "jmp -5"], "trace on\nc\nreset\ns");
+
+        let expected = "    0: acc +1     acc = 1\n    1: jmp -5     acc = 1\njumped outside the program\npc = 1, acc = 1, next = jmp -5\npc = 0, acc = 0, next = acc +1\n    0: acc +1     acc = 1\npc = 1, acc = 1, next = jmp -5\n";
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn overflowing_watches_and_accumulators_are_reported() {
+        let output = debug_program(
+            &["acc +2147483647", "acc +1"],
+            "watch acc + 1\nwatch pc ** 2\nc\nc\ns",
+        );
+
+        let expected = "watch 0: acc + 1 = 1\nerror: Unknown operator: **\nwatch 0: acc + 1 changed from 1 to overflow\npc = 1, acc = 2147483647, next = acc +1\naccumulator overflowed\npc = 1, acc = 2147483647, next = acc +1\naccumulator overflowed\npc = 1, acc = 2147483647, next = acc +1\n";
+        assert_eq!(output, expected);
+    }
+}
@@ -1,5 +1,17 @@
 use std::collections::HashSet;
+use std::fmt;
 use std::str::FromStr;
+use std::sync::Arc;
+
+use rayon::prelude::*;
+
+mod analysis;
+mod debugger;
+mod patch;
+mod repair;
+
+use debugger::Debugger;
+use patch::Patch;
 
 #[derive(Copy, Clone, Debug)]
 enum Instruction {
@@ -18,6 +30,32 @@ impl Instruction {
             Jump(v) => NoOperation(v),
         }
     }
+
+    /// Gets the index of the instruction executed after this one, if it is not before the start.
+    pub fn successor(self, index: usize) -> Option<usize> {
+        match self {
+            Instruction::Jump(v) => {
+                let target = index as i64 + i64::from(v);
+
+                if target < 0 {
+                    None
+                } else {
+                    Some(target as usize)
+                }
+            }
+            _ => Some(index + 1),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Instruction::NoOperation(v) => write!(f, "nop {:+}", v),
+            Instruction::Accumulate(v) => write!(f, "acc {:+}", v),
+            Instruction::Jump(v) => write!(f, "jmp {:+}", v),
+        }
+    }
 }
 
 impl From<&str> for Instruction {
@@ -35,67 +73,157 @@ impl From<&str> for Instruction {
     }
 }
 
+/// How a program stopped running, along with the final accumulator.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Outcome {
+    Terminated(i32),
+    Looped(i32),
+    /// A jump tried to move outside of the program.
+    Escaped(i32),
+    /// An `acc` instruction would have overflowed, leaving the accumulator as it was before.
+    Overflowed(i32),
+}
+
 #[derive(Clone, Debug, Default)]
 struct Machine {
-    instructions: Vec<Instruction>,
+    instructions: Arc<Vec<Instruction>>,
+    patch: Patch,
     accumulator: i32,
     program_counter: usize,
     visited: HashSet<usize>,
+    escaped: bool,
+    overflowed: bool,
 }
 
 impl Machine {
     pub fn new(instructions: Vec<Instruction>) -> Self {
         Self {
-            instructions,
+            instructions: Arc::new(instructions),
             ..Default::default()
         }
     }
 
+    /// Creates a fresh machine sharing this program, with a patch applied during execution.
+    pub fn with_patch(&self, patch: Patch) -> Self {
+        Self {
+            instructions: Arc::clone(&self.instructions),
+            patch,
+            ..Default::default()
+        }
+    }
+
+    /// Runs a fresh machine for each patch in parallel, returning the results of `simulate` in
+    /// the same order.
+    pub fn simulate_patches(&self, patches: &[Patch]) -> Vec<Outcome> {
+        patches
+            .par_iter()
+            .map(|patch| self.with_patch(patch.clone()).simulate())
+            .collect()
+    }
+
     pub fn simulate_until_loop(&mut self) -> i32 {
         loop {
-            if !self.visited.insert(self.program_counter) {
+            if self.has_looped()
+                || self.is_escaped()
+                || self.is_overflowed()
+                || self.is_terminated()
+            {
                 break self.accumulator;
             }
 
-            let current = self
-                .instructions
-                .get(self.program_counter)
-                .ok_or(self.accumulator)
-                .unwrap();
+            self.step();
+        }
+    }
 
-            // Perform instruction operation
-            match *current {
-                Instruction::Accumulate(v) => self.accumulator += v as i32,
-                _ => (),
-            }
+    /// Gets the instruction at the program counter, if it is within the program.
+    pub fn current(&self) -> Option<Instruction> {
+        self.instructions
+            .get(self.program_counter)
+            .map(|instruction| self.patch.apply(self.program_counter, *instruction))
+    }
+
+    /// Checks whether the instruction at the program counter has already been executed.
+    pub fn has_looped(&self) -> bool {
+        self.visited.contains(&self.program_counter)
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        self.program_counter == self.instructions.len()
+    }
+
+    /// Checks whether the last instruction executed tried to jump outside of the program, in
+    /// which case the program counter stays on that instruction.
+    pub fn is_escaped(&self) -> bool {
+        self.escaped
+    }
+
+    /// Checks whether the last instruction executed would have overflowed the accumulator, in
+    /// which case the program counter stays on that instruction.
+    pub fn is_overflowed(&self) -> bool {
+        self.overflowed
+    }
 
-            // Perform program counter manipulation
-            match *current {
-                Instruction::Jump(v) => {
-                    if v < 0 {
-                        self.program_counter -= (-v) as usize;
-                    } else {
-                        self.program_counter += v as usize;
-                    }
+    /// Executes the current instruction, doing nothing if the machine has terminated, escaped or
+    /// overflowed.
+    pub fn step(&mut self) {
+        let current = match self.current() {
+            Some(current) if !self.escaped && !self.overflowed => current,
+            _ => return,
+        };
+
+        // Perform instruction operation
+        if let Instruction::Accumulate(v) = current {
+            match self.accumulator.checked_add(v) {
+                Some(accumulator) => self.accumulator = accumulator,
+                None => {
+                    self.overflowed = true;
+                    return;
                 }
-                _ => self.program_counter += 1,
             }
         }
+
+        self.visited.insert(self.program_counter);
+
+        // Perform program counter manipulation
+        let next = current
+            .successor(self.program_counter)
+            .filter(|next| *next <= self.instructions.len());
+
+        match next {
+            Some(next) => self.program_counter = next,
+            None => self.escaped = true,
+        }
+    }
+
+    /// Runs the program until it terminates, loops, jumps outside of the program or overflows.
+    pub fn simulate(&mut self) -> Outcome {
+        loop {
+            if self.is_terminated() {
+                break Outcome::Terminated(self.accumulator);
+            }
+
+            if self.is_escaped() {
+                break Outcome::Escaped(self.accumulator);
+            }
+
+            if self.is_overflowed() {
+                break Outcome::Overflowed(self.accumulator);
+            }
+
+            if self.has_looped() {
+                break Outcome::Looped(self.accumulator);
+            }
+
+            self.step();
+        }
     }
 
     pub fn reset(&mut self) {
         self.program_counter = Default::default();
         self.accumulator = Default::default();
         self.visited = Default::default();
-    }
-
-    pub fn flip(&mut self, index: usize) {
-        // If this isn't the first run, restore the previous index
-        if index != 0 {
-            self.instructions[index - 1] = self.instructions[index - 1].flip();
-        }
-
-        self.instructions[index] = self.instructions[index].flip();
+        self.escaped = false;
+        self.overflowed = false;
     }
 }
 
@@ -108,14 +236,48 @@ fn main() {
     let lines: Vec<_> = input.trim().lines().collect();
 
     let instructions = parse_input(&lines);
-    let mut machine = Machine::new(instructions);
+    let mut machine = Machine::new(instructions.clone());
+
+    match std::env::args().nth(1).as_deref() {
+        Some("debug") => {
+            let stdin = std::io::stdin();
+            let mut debugger = Debugger::new(machine);
+            debugger.run(stdin.lock(), std::io::stdout()).unwrap();
+            return;
+        }
+        Some("analyse") => {
+            println!("{}", analysis::analyse(&instructions));
+            return;
+        }
+        Some("dot") => {
+            print!("{}", analysis::analyse(&instructions).to_dot(&instructions));
+            return;
+        }
+        Some("brute") => {
+            // Try flipping every instruction, which is slower but doesn't rely on the analysis
+            let patches: Vec<_> = (0..instructions.len()).map(Patch::flip).collect();
+
+            for (index, result) in machine.simulate_patches(&patches).iter().enumerate() {
+                if let Outcome::Terminated(value) = result {
+                    println!("Flipping instruction {} terminates with {}", index, value);
+                }
+            }
+
+            return;
+        }
+        _ => (),
+    }
 
     let value = machine.simulate_until_loop();
     println!("Part 1 Solution: {}", value);
 
-    for i in 0..lines.len() {
-        machine.reset();
-        machine.flip(i);
-        let value = machine.simulate_until_loop();
-    }
+    // Programs that already terminate don't need anything flipping
+    let patch = repair::find_repair(&instructions).map_or_else(Patch::default, Patch::flip);
+
+    let value = match machine.with_patch(patch).simulate() {
+        Outcome::Terminated(value) => value,
+        _ => panic!("Failed to find an instruction to flip"),
+    };
+
+    println!("Part 2 Solution: {}", value);
 }
@@ -0,0 +1,120 @@
+use std::collections::BTreeSet;
+
+use crate::Instruction;
+
+/// A set of changes to a program that is applied as instructions are executed, leaving the
+/// program itself untouched.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Patch {
+    flipped: BTreeSet<usize>,
+}
+
+impl Patch {
+    /// Creates a patch that flips the instruction at a single index.
+    pub fn flip(index: usize) -> Self {
+        Self::default().and_flip(index)
+    }
+
+    pub fn and_flip(mut self, index: usize) -> Self {
+        self.flipped.insert(index);
+        self
+    }
+
+    pub fn apply(&self, index: usize, instruction: Instruction) -> Instruction {
+        if self.flipped.contains(&index) {
+            instruction.flip()
+        } else {
+            instruction
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_input, Machine, Outcome};
+
+    const EXAMPLE: &[&str] = &[
+        "nop +0", "acc +1", "jmp +4", "acc +3", "jmp -3", "acc -99", "acc +1", "jmp -4", "acc +6",
+    ];
+
+    fn listing(machine: &Machine) -> Vec<String> {
+        machine.instructions.iter().map(|i| i.to_string()).collect()
+    }
+
+    #[test]
+    fn patches_can_flip_several_instructions_in_any_order() {
+        let patch = Patch::flip(7).and_flip(2).and_flip(4);
+        assert_eq!(patch, Patch::flip(2).and_flip(4).and_flip(7));
+
+        let instructions = parse_input(EXAMPLE);
+
+        let patched: Vec<_> = instructions
+            .iter()
+            .enumerate()
+            .map(|(i, instruction)| patch.apply(i, *instruction).to_string())
+            .collect();
+
+        let mut expected: Vec<_> = EXAMPLE.iter().map(|s| s.to_string()).collect();
+        expected[2] = String::from("nop +4");
+        expected[4] = String::from("nop -3");
+        expected[7] = String::from("nop -4");
+
+        assert_eq!(patched, expected);
+    }
+
+    #[test]
+    fn patches_are_simulated_without_changing_the_program() {
+        let machine = Machine::new(parse_input(EXAMPLE));
+
+        let patches = vec![
+            Patch::flip(7),
+            Patch::flip(4),
+            Patch::flip(0),
+            Patch::flip(7).and_flip(0),
+            Patch::default(),
+            Patch::flip(7),
+        ];
+
+        let outcomes = machine.simulate_patches(&patches);
+
+        let expected = vec![
+            Outcome::Terminated(8),
+            Outcome::Looped(-94),
+            Outcome::Looped(0),
+            Outcome::Looped(0),
+            Outcome::Looped(5),
+            Outcome::Terminated(8),
+        ];
+
+        assert_eq!(outcomes, expected);
+
+        // Every patched machine shared the original program rather than copying it
+        assert_eq!(listing(&machine), EXAMPLE);
+        assert_eq!(std::sync::Arc::strong_count(&machine.instructions), 1);
+        assert_eq!(machine.clone().simulate(), Outcome::Looped(5));
+    }
+
+    #[test]
+    fn patches_that_jump_outside_the_program_escape() {
+        let machine = Machine::new(parse_input(&["nop -5", "jmp -1"]));
+
+        let patches: Vec<_> = (0..2).map(Patch::flip).collect();
+        let outcomes = machine.simulate_patches(&patches);
+
+        assert_eq!(outcomes, vec![Outcome::Escaped(0), Outcome::Terminated(0)]);
+        assert_eq!(machine.clone().simulate(), Outcome::Looped(0));
+    }
+
+    #[test]
+    fn patches_that_overflow_the_accumulator_stop() {
+        let machine = Machine::new(parse_input(&["acc +2147483647", "jmp +2", "acc +1"]));
+
+        let outcomes = machine.simulate_patches(&[Patch::default(), Patch::flip(1)]);
+
+        assert_eq!(
+            outcomes,
+            vec![Outcome::Terminated(i32::MAX), Outcome::Overflowed(i32::MAX)]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use crate::Instruction;
+
+/// Builds the reversed control-flow graph, where index `instructions.len()` represents
+/// termination and jumps outside of the program have no edge.
+fn predecessors(instructions: &[Instruction]) -> Vec<Vec<usize>> {
+    let end = instructions.len();
+    let mut predecessors = vec![Vec::new(); end + 1];
+
+    for (i, instruction) in instructions.iter().enumerate() {
+        if let Some(next) = instruction.successor(i).filter(|next| *next <= end) {
+            predecessors[next].push(i);
+        }
+    }
+
+    predecessors
+}
+
+/// Finds which instructions lead to termination if the program starts executing from them.
+pub fn terminating(instructions: &[Instruction]) -> Vec<bool> {
+    let predecessors = predecessors(instructions);
+    let mut terminates = vec![false; instructions.len() + 1];
+
+    // Work backwards from the end of the program
+    let mut stack = vec![instructions.len()];
+    terminates[instructions.len()] = true;
+
+    while let Some(current) = stack.pop() {
+        for &previous in &predecessors[current] {
+            if !terminates[previous] {
+                terminates[previous] = true;
+                stack.push(previous);
+            }
+        }
+    }
+
+    terminates
+}
+
+/// Finds the instruction that can be flipped to make the program terminate, in linear time.
+///
+/// Returns `None` if no single flip works, which includes programs that already terminate
+/// without needing one.
+pub fn find_repair(instructions: &[Instruction]) -> Option<usize> {
+    let terminates = terminating(instructions);
+
+    if terminates[0] {
+        return None;
+    }
+
+    let mut visited = vec![false; instructions.len()];
+    let mut current = 0;
+
+    // Follow the original program, which only reaches instructions that run before the first loop
+    while current < instructions.len() && !visited[current] {
+        visited[current] = true;
+
+        let instruction = instructions[current];
+
+        let flipped = instruction
+            .flip()
+            .successor(current)
+            .filter(|next| *next <= instructions.len());
+
+        // Accumulate instructions flip to themselves, so can never be the repair
+        let flippable = !matches!(instruction, Instruction::Accumulate(_));
+
+        if flippable && flipped.is_some_and(|next| terminates[next]) {
+            return Some(current);
+        }
+
+        current = instruction.successor(current)?;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patch::Patch;
+    use crate::{parse_input, Machine, Outcome};
+
+    #[test]
+    fn the_example_is_repaired_by_flipping_the_last_jump() {
+        let program = parse_input(&[
+            "nop +0", "acc +1", "jmp +4", "acc +3", "jmp -3", "acc -99", "acc +1", "jmp -4",
+            "acc +6",
+        ]);
+
+        assert_eq!(find_repair(&program), Some(7));
+    }
+
+    #[test]
+    fn programs_that_already_terminate_need_no_repair() {
+        let program = parse_input(&["acc +1", "acc +2"]);
+
+        assert_eq!(terminating(&program), vec![true, true, true]);
+        assert_eq!(find_repair(&program), None);
+        // Flipping the first instruction would break a program that already works
+        let program = parse_input(&["nop +2", "jmp +2", "jmp -2"]);
+        let machine = Machine::new(program.clone());
+
+        assert_eq!(machine.clone().simulate(), Outcome::Terminated(0));
+        assert_eq!(
+            machine.with_patch(Patch::flip(0)).simulate(),
+            Outcome::Looped(0)
+        );
+        assert_eq!(find_repair(&program), None);
+    }
+
+    #[test]
+    fn no_operations_can_be_flipped_into_jumps() {
+        let program = parse_input(&["nop +3", "jmp -1", "jmp -1", "acc +1"]);
+
+        assert_eq!(terminating(&program), vec![false, false, false, true, true]);
+        assert_eq!(find_repair(&program), Some(0));
+    }
+
+    #[test]
+    fn large_programs_agree_with_brute_force() {
+        for seed in 0..5u64 {
+            let mut state = seed;
+            let mut random = |range: i32| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as i32 % range
+            };
+
+            // Only jumping forwards means the program terminates
+            let length = 2000;
+            let mut program: Vec<_> = (0..length)
+                .map(|i| match random(3) {
+                    0 => Instruction::NoOperation(random(41) - 20),
+                    1 => Instruction::Accumulate(random(41) - 20),
+                    _ => Instruction::Jump((random(5) + 1).min(length - i)),
+                })
+                .collect();
+
+            // Break it by turning a backwards `nop` on the path into a jump, which must loop
+            let mut current = 0;
+            let mut broken = None;
+
+            while current < program.len() {
+                if let Instruction::NoOperation(v) = program[current] {
+                    if v < 0 && current as i32 + v >= 0 && random(20) == 0 {
+                        broken = Some(current);
+                        program[current] = Instruction::Jump(v);
+                        break;
+                    }
+                }
+
+                current = program[current].successor(current).unwrap();
+            }
+
+            let broken = broken.expect("Failed to break the program");
+            let machine = Machine::new(program.clone());
+            assert!(matches!(machine.clone().simulate(), Outcome::Looped(_)));
+
+            let patches: Vec<_> = (0..program.len()).map(Patch::flip).collect();
+            let fixes: Vec<_> = machine
+                .simulate_patches(&patches)
+                .iter()
+                .enumerate()
+                .filter(|(_, outcome)| matches!(outcome, Outcome::Terminated(_)))
+                .map(|(index, _)| index)
+                .collect();
+
+            assert!(fixes.contains(&broken));
+            assert!(fixes.contains(&find_repair(&program).unwrap()));
+        }
+    }
+}
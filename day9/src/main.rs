@@ -1,70 +1,238 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
 use std::str::FromStr;
 
-fn find_sum_pair(subset: &[usize], value: usize) -> Option<(usize, usize)> {
-    for i in 0..subset.len() {
-        for j in 0..subset.len() {
-            if i == j {
-                continue;
-            }
+use num_bigint::BigInt;
 
-            let (x, y) = (subset[i], subset[j]);
+mod number;
+mod validator;
+mod window;
 
-            if x + y == value {
-                return Some((x, y));
-            }
+use number::Number;
+use validator::Validator;
+
+/// Finds at least 2 contiguous numbers that add up to `to_find`, using prefix sums so that
+/// negative numbers are supported.
+///
+/// The prefix sums are kept as bignums, as they can overflow the type even when a set exists.
+fn find_contiguous_set<'a, T: Number>(values: &'a [T], to_find: &T) -> Option<&'a [T]> {
+    let to_find = to_find.to_bigint();
+    let mut sums = vec![BigInt::from(0)];
+    let mut earliest = HashMap::new();
+
+    for (i, value) in values.iter().enumerate() {
+        sums.push(&sums[i] + value.to_bigint());
+
+        let end = i + 1;
+
+        if end < 2 {
+            continue;
         }
-    }
 
-    None
-}
+        // Only sums from at least 2 values back can start the set
+        earliest.entry(sums[end - 2].clone()).or_insert(end - 2);
 
-fn find_first_violation(values: &[usize], window_size: usize) -> Option<usize> {
-    for i in window_size..values.len() {
-        if find_sum_pair(&values[i - window_size..i], values[i]).is_none() {
-            return Some(values[i]);
+        if let Some(start) = earliest.get(&(&sums[end] - &to_find)) {
+            return Some(&values[*start..end]);
         }
     }
 
     None
 }
 
-fn find_contiguous_set(values: &[usize], to_find: usize) -> Option<&[usize]> {
-    // Try each index
-    for i in 0..values.len() {
-        let mut sum = values[i];
+fn solve<T: Number>(input: &str, window_size: usize) -> Result<(T, T), String> {
+    let mut violations = Validator::new(input.as_bytes(), window_size)?;
+
+    let (_, first_violation) = violations
+        .next()
+        .ok_or("Failed to find anything that violated the rules")??;
+
+    let values = input
+        .lines()
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .map(|x| T::from_str(x).map_err(|_| format!("Invalid number: {}", x)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let contiguous_set =
+        find_contiguous_set(&values, &first_violation).ok_or("Failed to find a contigious set")?;
+
+    let min = contiguous_set.iter().min().unwrap();
+    let max = contiguous_set.iter().max().unwrap();
 
-        for j in i + 1..values.len() {
-            sum += values[j];
+    let weakness = min
+        .checked_add(max)
+        .ok_or(format!("Overflow adding {} and {}", min, max))?;
 
-            if sum == to_find {
-                return Some(&values[i..=j]);
-            } else if sum > to_find {
-                break;
-            }
+    Ok((first_violation, weakness))
+}
+
+fn report<T: Number>(solution: Result<(T, T), String>) {
+    match solution {
+        Ok((first_violation, weakness)) => {
+            println!("Part 1 Solution: {}", first_violation);
+            println!("Part 2 Solution: {}", weakness);
         }
+        Err(e) => eprintln!("Error: {}", e),
     }
+}
 
-    None
+/// Writes each violation as soon as it is read, without holding on to the rest of the input.
+fn stream<T: Number, R: BufRead, W: Write>(
+    reader: R,
+    window_size: usize,
+    mut output: W,
+) -> Result<(), String> {
+    for violation in Validator::<R, T>::new(reader, window_size)? {
+        let (index, value) = violation?;
+        writeln!(output, "{}: {}", index, value).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
 }
 
 fn main() {
+    let mut args: Vec<_> = std::env::args().skip(1).collect();
+
+    // `violations` reads from stdin and lists every violation instead of solving the puzzle
+    let streaming = args.first().map(String::as_str) == Some("violations");
+
+    if streaming {
+        args.remove(0);
+    }
+
+    let window_size = args
+        .first()
+        .map(|x| usize::from_str(x).expect("Window size should be a number"))
+        .unwrap_or(25);
+
+    if streaming {
+        let stdin = std::io::stdin();
+        let stdout = std::io::stdout();
+
+        let result = match args.get(1).map(String::as_str) {
+            None | Some("i64") => stream::<i64, _, _>(stdin.lock(), window_size, stdout.lock()),
+            Some("u128") => stream::<u128, _, _>(stdin.lock(), window_size, stdout.lock()),
+            Some("bignum") => stream::<BigInt, _, _>(stdin.lock(), window_size, stdout.lock()),
+            Some(other) => Err(format!("Unknown number type: {}", other)),
+        };
+
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+        }
+
+        return;
+    }
+
     let input = std::fs::read_to_string("input.txt").unwrap();
-    let values: Vec<_> = input
-        .trim()
-        .lines()
-        .map(|x| usize::from_str(x).unwrap())
-        .collect();
 
-    let first_violation =
-        find_first_violation(&values, 25).expect("Failed to find anything that violated the rules");
+    match args.get(1).map(String::as_str) {
+        None | Some("i64") => report(solve::<i64>(&input, window_size)),
+        Some("u128") => report(solve::<u128>(&input, window_size)),
+        Some("bignum") => report(solve::<BigInt>(&input, window_size)),
+        Some(other) => eprintln!("Unknown number type: {}", other),
+    }
+}
 
-    println!("Part 1 Solution: {}", first_violation);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &[i64] = &[
+        35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309, 576,
+    ];
+
+    #[test]
+    fn the_example_has_a_contiguous_set() {
+        assert_eq!(
+            find_contiguous_set(EXAMPLE, &127),
+            Some(&[15, 25, 47, 40][..])
+        );
+        assert_eq!(find_contiguous_set(EXAMPLE, &1), None);
+    }
 
-    let contiguous_set =
-        find_contiguous_set(&values, first_violation).expect("Failed to find a contigious set");
+    #[test]
+    fn contiguous_sets_have_at_least_two_values() {
+        // A single value is never a set on its own
+        assert_eq!(find_contiguous_set(&[5i64, 62, 1], &62), None);
+        assert_eq!(find_contiguous_set(&[5i64, 62, 1], &63), Some(&[62, 1][..]));
+        assert_eq!(find_contiguous_set::<i64>(&[], &0), None);
+    }
 
-    let min = contiguous_set.iter().min().unwrap();
-    let max = contiguous_set.iter().max().unwrap();
+    #[test]
+    fn contiguous_sets_can_contain_negative_and_duplicate_values() {
+        assert_eq!(
+            find_contiguous_set(&[4i64, -3, 10, -3, 2], &6),
+            Some(&[-3, 10, -3, 2][..])
+        );
+        assert_eq!(find_contiguous_set(&[3i64, 3, 3], &6), Some(&[3, 3][..]));
+    }
+
+    #[test]
+    fn sets_are_found_even_when_earlier_prefixes_overflow() {
+        let values = [i64::MAX, 1, 2, 3];
+        assert_eq!(find_contiguous_set(&values, &5), Some(&[2, 3][..]));
+
+        let values = [i64::MIN, -1, 3, 4];
+        assert_eq!(find_contiguous_set(&values, &7), Some(&[3, 4][..]));
+    }
 
-    println!("Part 2 Solution: {}", min + max);
+    #[test]
+    fn every_number_type_finds_the_same_answer() {
+        let input: String = EXAMPLE.iter().map(|x| format!("{}\n", x)).collect();
+
+        assert_eq!(solve::<i64>(&input, 5), Ok((127, 62)));
+        assert_eq!(solve::<u128>(&input, 5), Ok((127, 62)));
+        assert_eq!(
+            solve::<BigInt>(&input, 5),
+            Ok((BigInt::from(127), BigInt::from(62)))
+        );
+    }
+
+    #[test]
+    fn values_beyond_the_type_are_only_handled_by_bignums() {
+        let big = u128::MAX / 2;
+        let input = format!("{}\n1\n2\n{}\n", big, big + 3);
+
+        assert_eq!(
+            solve::<i64>(&input, 3),
+            Err(format!("Line 1: invalid number {}", big))
+        );
+        assert_eq!(solve::<u128>(&input, 3), Ok((big + 3, big + 1)));
+
+        let huge = BigInt::from(u128::MAX) * 4;
+        let input = format!("{}\n1\n2\n{}\n", huge, &huge + 3);
+
+        assert!(solve::<u128>(&input, 3).is_err());
+        assert_eq!(solve::<BigInt>(&input, 3), Ok((&huge + 3, &huge + 1)));
+    }
+
+    #[test]
+    fn inputs_without_violations_are_errors() {
+        assert_eq!(
+            solve::<i64>("1\n2\n3\n5\n", 2),
+            Err(String::from(
+                "Failed to find anything that violated the rules"
+            ))
+        );
+        assert!(solve::<u128>("1\n2\n-3\n", 2).is_err());
+    }
+
+    #[test]
+    fn every_violation_is_streamed() {
+        let input = "1\n2\n4\n6\n\n11\n18\n";
+        let mut output = Vec::new();
+
+        stream::<i64, _, _>(input.as_bytes(), 2, &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "2: 4\n4: 11\n5: 18\n");
+
+        // Violations found before an invalid line are still written
+        let mut output = Vec::new();
+
+        assert_eq!(
+            stream::<u128, _, _>("1\n2\n4\n-1\n".as_bytes(), 2, &mut output),
+            Err(String::from("Line 4: invalid number -1"))
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), "2: 4\n");
+    }
 }
@@ -0,0 +1,66 @@
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+
+/// A number that the cipher can be analysed over, which must detect overflow rather than wrap.
+pub trait Number: Clone + Debug + Display + Eq + Hash + Ord + FromStr {
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+
+    /// Converts the number to one that can't overflow, for sums that may exceed the type.
+    fn to_bigint(&self) -> BigInt;
+}
+
+macro_rules! impl_number {
+    ($($t:ty),*) => {
+        $(
+            impl Number for $t {
+                fn checked_add(&self, other: &Self) -> Option<Self> {
+                    <$t>::checked_add(*self, *other)
+                }
+
+                fn to_bigint(&self) -> BigInt {
+                    BigInt::from(*self)
+                }
+            }
+        )*
+    };
+}
+
+impl_number!(i64, u64, u128, usize);
+
+impl Number for BigInt {
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn to_bigint(&self) -> BigInt {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_width_numbers_detect_overflow() {
+        assert_eq!(Number::checked_add(&i64::MAX, &1), None);
+        assert_eq!(Number::checked_add(&i64::MIN, &-1), None);
+        assert_eq!(Number::checked_add(&u128::MAX, &0), Some(u128::MAX));
+        assert_eq!(Number::checked_add(&u128::MAX, &1), None);
+        assert_eq!(Number::checked_add(&u64::MAX, &1), None);
+        assert_eq!(Number::checked_add(&usize::MAX, &1), None);
+    }
+
+    #[test]
+    fn bignums_never_overflow() {
+        let max = BigInt::from(u128::MAX);
+        let sum = Number::checked_add(&max, &max).unwrap();
+
+        assert_eq!(sum, BigInt::from(u128::MAX) * 2);
+        assert_eq!(sum.to_bigint(), sum);
+        assert_eq!(i64::MIN.to_bigint(), BigInt::from(i64::MIN));
+    }
+}
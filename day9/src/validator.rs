@@ -0,0 +1,105 @@
+use std::io::{BufRead, Lines};
+
+use crate::number::Number;
+use crate::window::Window;
+
+/// Reads numbers one line at a time, yielding the index and value of every number that is not
+/// the sum of two of the numbers in the window before it.
+pub struct Validator<R, T> {
+    lines: Lines<R>,
+    window: Window<T>,
+    line: usize,
+    index: usize,
+}
+
+impl<R: BufRead, T: Number> Validator<R, T> {
+    pub fn new(reader: R, window_size: usize) -> Result<Self, String> {
+        Ok(Self {
+            lines: reader.lines(),
+            window: Window::new(window_size)?,
+            line: 0,
+            index: 0,
+        })
+    }
+}
+
+impl<R: BufRead, T: Number> Iterator for Validator<R, T> {
+    type Item = Result<(usize, T), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for line in &mut self.lines {
+            self.line += 1;
+
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.to_string())),
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let line = line.trim();
+
+            let value = match T::from_str(line) {
+                Ok(value) => value,
+                Err(_) => {
+                    return Some(Err(format!("Line {}: invalid number {}", self.line, line)));
+                }
+            };
+
+            let index = self.index;
+            let violation = self.window.is_full() && !self.window.contains_sum(&value);
+
+            self.index += 1;
+            self.window.push(value.clone());
+
+            if violation {
+                return Some(Ok((index, value)));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violations(input: &str, window_size: usize) -> Vec<Result<(usize, i64), String>> {
+        Validator::new(input.as_bytes(), window_size)
+            .unwrap()
+            .collect()
+    }
+
+    #[test]
+    fn every_violation_is_reported() {
+        let input = "35\n20\n15\n25\n47\n40\n62\n55\n65\n95\n102\n117\n150\n182\n127\n219\n299\n277\n309\n576\n";
+
+        assert_eq!(violations(input, 5), vec![Ok((14, 127))]);
+        assert_eq!(
+            violations("1\n2\n4\n6\n11\n", 2),
+            vec![Ok((2, 4)), Ok((4, 11))]
+        );
+    }
+
+    #[test]
+    fn blank_lines_are_skipped_without_changing_indices() {
+        let violations = violations("1\n\n2\n   \n3\n\n7\n", 2);
+
+        assert_eq!(violations, vec![Ok((3, 7))]);
+    }
+
+    #[test]
+    fn invalid_numbers_are_reported_with_line_numbers() {
+        let violations = violations("1\n\n2\n  abc  \n4\n", 2);
+
+        assert_eq!(
+            violations,
+            vec![Err(String::from("Line 4: invalid number abc")), Ok((2, 4))]
+        );
+
+        assert!(Validator::<_, i64>::new("1\n".as_bytes(), 1).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::number::Number;
+
+/// The most recent values in the stream, along with every sum of two of them.
+#[derive(Clone, Debug)]
+pub struct Window<T> {
+    values: VecDeque<T>,
+    sums: HashMap<T, usize>,
+    capacity: usize,
+}
+
+impl<T: Number> Window<T> {
+    /// Creates an empty window, which needs room for at least 2 values to contain any sums.
+    pub fn new(capacity: usize) -> Result<Self, String> {
+        if capacity < 2 {
+            return Err(format!("Window size must be at least 2, got {}", capacity));
+        }
+
+        Ok(Self {
+            values: VecDeque::with_capacity(capacity),
+            sums: HashMap::new(),
+            capacity,
+        })
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.capacity
+    }
+
+    /// Adds a value to the window, removing the oldest one if it is already full.
+    ///
+    /// Sums that overflow are not stored, as no value of the same type could ever equal them.
+    pub fn push(&mut self, value: T) {
+        if self.is_full() {
+            let oldest = self.values.pop_front().unwrap();
+
+            for other in &self.values {
+                if let Some(sum) = oldest.checked_add(other) {
+                    let count = self.sums.get_mut(&sum).unwrap();
+                    *count -= 1;
+
+                    if *count == 0 {
+                        self.sums.remove(&sum);
+                    }
+                }
+            }
+        }
+
+        for other in &self.values {
+            if let Some(sum) = value.checked_add(other) {
+                *self.sums.entry(sum).or_default() += 1;
+            }
+        }
+
+        self.values.push_back(value);
+    }
+
+    /// Checks whether two different entries in the window add up to `value`.
+    pub fn contains_sum(&self, value: &T) -> bool {
+        self.sums.contains_key(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(capacity: usize, values: &[i64]) -> Window<i64> {
+        let mut window = Window::new(capacity).unwrap();
+
+        for value in values {
+            window.push(*value);
+        }
+
+        window
+    }
+
+    #[test]
+    fn windows_need_room_for_two_values() {
+        assert!(Window::<i64>::new(0).is_err());
+        assert!(Window::<i64>::new(1).is_err());
+        assert!(Window::<i64>::new(2).is_ok());
+    }
+
+    #[test]
+    fn sums_are_forgotten_when_values_leave_the_window() {
+        let mut window = filled(3, &[1, 2, 3]);
+
+        assert!(window.is_full());
+        assert!(window.contains_sum(&3));
+        assert!(window.contains_sum(&5));
+        assert!(!window.contains_sum(&2));
+
+        window.push(10);
+
+        assert!(!window.contains_sum(&3));
+        assert!(!window.contains_sum(&4));
+        assert!(window.contains_sum(&5));
+        assert!(window.contains_sum(&13));
+    }
+
+    #[test]
+    fn duplicate_values_are_counted_separately() {
+        let mut window = filled(3, &[1, 1, 2]);
+
+        // Both 1s can be added together, and each of them can be added to 2
+        assert!(window.contains_sum(&2));
+        assert!(window.contains_sum(&3));
+
+        window.push(4);
+
+        assert!(!window.contains_sum(&2));
+        assert!(window.contains_sum(&3));
+
+        window.push(4);
+
+        assert!(!window.contains_sum(&3));
+        assert!(window.contains_sum(&8));
+        assert!(window.contains_sum(&6));
+
+        // A single value can't be added to itself
+        assert!(!filled(2, &[5, 1]).contains_sum(&10));
+        assert!(filled(2, &[5, 5]).contains_sum(&10));
+    }
+
+    #[test]
+    fn overflowing_sums_are_skipped() {
+        let mut window = Window::new(2).unwrap();
+        window.push(u64::MAX);
+        window.push(1);
+        window.push(2);
+
+        assert!(window.contains_sum(&3));
+        assert!(!window.contains_sum(&0));
+    }
+}
@@ -0,0 +1,172 @@
+//! A general purpose virtual machine for handheld console programs, with an instruction set
+//! that can be extended by registering new [`Operation`]s.
+//!
+//! This is standalone rather than a replacement for the `Machine` in day 8, whose debugger,
+//! patching and analysis are all written against the three boot code instructions.
+
+use std::fmt;
+use std::str::FromStr;
+
+mod machine;
+mod operations;
+mod set;
+
+pub use machine::{Machine, Outcome};
+pub use set::{Effect, InstructionSet, Operation, Parameter};
+
+/// The number of general purpose registers available to a program.
+pub const REGISTERS: usize = 8;
+
+/// The register that `acc` instructions operate on.
+pub const ACCUMULATOR: usize = 0;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Operand {
+    Immediate(i64),
+    Register(usize),
+}
+
+impl Operand {
+    /// Gets the value of the operand given the current machine state.
+    pub fn resolve(&self, state: &State) -> i64 {
+        match *self {
+            Operand::Immediate(v) => v,
+            Operand::Register(r) => state.registers[r],
+        }
+    }
+}
+
+impl FromStr for Operand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(index) = s.strip_prefix('r') {
+            let register =
+                usize::from_str(index).map_err(|_| format!("Invalid register: {}", s))?;
+
+            if register >= REGISTERS {
+                return Err(format!("Register {} does not exist", s));
+            }
+
+            return Ok(Operand::Register(register));
+        }
+
+        i64::from_str(s)
+            .map(Operand::Immediate)
+            .map_err(|_| format!("Invalid operand: {}", s))
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Operand::Immediate(v) => write!(f, "{:+}", v),
+            Operand::Register(r) => write!(f, "r{}", r),
+        }
+    }
+}
+
+/// A decoded instruction, referring to an operation by its index in an [`InstructionSet`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Instruction {
+    pub opcode: usize,
+    pub operands: Vec<Operand>,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct State {
+    pub registers: [i64; REGISTERS],
+    pub program_counter: usize,
+}
+
+impl State {
+    pub fn accumulator(&self) -> i64 {
+        self.registers[ACCUMULATOR]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOOT_CODE: &str =
+        "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    #[test]
+    fn boot_code_loops_with_the_expected_accumulator() {
+        let set = InstructionSet::standard();
+        let program = set.assemble(BOOT_CODE).unwrap();
+        let mut machine = Machine::new(&set, program);
+
+        assert_eq!(machine.run_until_loop(), Outcome::Looped);
+        assert_eq!(machine.state().accumulator(), 5);
+    }
+
+    #[test]
+    fn registers_and_conditional_jumps_can_be_used() {
+        let set = InstructionSet::standard();
+        let source = "mov r1 +5\nacc r1\nadd r1 -1\njnz r1 -2";
+        let program = set.assemble(source).unwrap();
+        let mut machine = Machine::new(&set, program);
+
+        assert_eq!(machine.run(), Outcome::Terminated);
+        assert_eq!(machine.state().accumulator(), 15);
+    }
+
+    #[test]
+    fn programs_can_be_round_tripped() {
+        let set = InstructionSet::standard();
+        let source = "nop +0\nmov r2 -7\njz r2 +3\nhlt";
+        let program = set.assemble(source).unwrap();
+
+        assert_eq!(set.disassemble(&program), source);
+    }
+
+    #[test]
+    fn unknown_mnemonics_are_reported_with_line_numbers() {
+        let set = InstructionSet::standard();
+        let error = set.assemble("nop +0\nxyz +1").unwrap_err();
+
+        assert_eq!(error, "Line 2: Unknown operation: xyz");
+    }
+
+    #[test]
+    fn overflowing_registers_fault_instead_of_panicking() {
+        let set = InstructionSet::standard();
+
+        for source in &[
+            "acc +9223372036854775807\nacc +1",
+            "mov r1 -2\nmul r1 +9223372036854775807",
+        ] {
+            let program = set.assemble(source).unwrap();
+            let mut machine = Machine::new(&set, program);
+
+            assert!(matches!(machine.run(), Outcome::Faulted(_)));
+        }
+
+        let program = set
+            .assemble("add r3 +9223372036854775807\nadd r3 +1")
+            .unwrap();
+        let mut machine = Machine::new(&set, program);
+
+        assert_eq!(
+            machine.run(),
+            Outcome::Faulted(String::from(
+                "add overflowed r3 combining 9223372036854775807 with 1"
+            ))
+        );
+        assert_eq!(machine.state().registers[3], i64::MAX);
+    }
+
+    #[test]
+    fn jumps_outside_the_program_are_reported() {
+        let set = InstructionSet::standard();
+
+        for source in &["jmp -1", "nop +0\njmp +2", "jmp +9223372036854775807"] {
+            let program = set.assemble(source).unwrap();
+            let mut machine = Machine::new(&set, program);
+
+            assert_eq!(machine.run(), Outcome::OutOfBounds);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+
+use crate::{Effect, Instruction, InstructionSet, State};
+
+/// Why a machine stopped running.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// The program counter moved to just after the last instruction.
+    Terminated,
+    /// A `hlt` style operation was executed.
+    Halted,
+    /// An instruction was about to be executed for the second time.
+    Looped,
+    /// A jump moved the program counter outside of the program.
+    OutOfBounds,
+    /// An operation failed, such as by overflowing a register.
+    Faulted(String),
+}
+
+pub struct Machine<'a> {
+    set: &'a InstructionSet,
+    program: Vec<Instruction>,
+    state: State,
+}
+
+impl<'a> Machine<'a> {
+    pub fn new(set: &'a InstructionSet, program: Vec<Instruction>) -> Self {
+        Self {
+            set,
+            program,
+            state: Default::default(),
+        }
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    pub fn reset(&mut self) {
+        self.state = Default::default();
+    }
+
+    /// Executes the current instruction, returning the outcome if the machine has stopped.
+    pub fn step(&mut self) -> Option<Outcome> {
+        let instruction = match self.program.get(self.state.program_counter) {
+            Some(instruction) => instruction,
+            None => return Some(Outcome::Terminated),
+        };
+
+        let operation = self.set.operation(instruction.opcode);
+
+        let offset = match operation.execute(&instruction.operands, &mut self.state) {
+            Ok(Effect::Next) => 1,
+            Ok(Effect::Jump(offset)) => offset,
+            Ok(Effect::Halt) => return Some(Outcome::Halted),
+            Err(e) => return Some(Outcome::Faulted(e)),
+        };
+
+        let target = (self.state.program_counter as i64).checked_add(offset);
+
+        match target {
+            Some(target) if target >= 0 && target as usize <= self.program.len() => {
+                self.state.program_counter = target as usize;
+            }
+            _ => return Some(Outcome::OutOfBounds),
+        }
+
+        None
+    }
+
+    /// Runs the program until it stops, which may be never.
+    pub fn run(&mut self) -> Outcome {
+        loop {
+            if let Some(outcome) = self.step() {
+                break outcome;
+            }
+        }
+    }
+
+    /// Runs the program until it stops or is about to execute an instruction for a second time.
+    pub fn run_until_loop(&mut self) -> Outcome {
+        let mut visited = HashSet::new();
+
+        loop {
+            if !visited.insert(self.state.program_counter) {
+                break Outcome::Looped;
+            }
+
+            if let Some(outcome) = self.step() {
+                break outcome;
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{Effect, Operand, Operation, Parameter, State, ACCUMULATOR};
+
+fn destination(operand: &Operand) -> usize {
+    match *operand {
+        Operand::Register(r) => r,
+        Operand::Immediate(_) => unreachable!(),
+    }
+}
+
+/// Combines a register with a value, failing instead of overflowing.
+fn store<O: Operation>(
+    operation: &O,
+    state: &mut State,
+    register: usize,
+    combine: fn(i64, i64) -> Option<i64>,
+    value: i64,
+) -> Result<Effect, String> {
+    let current = state.registers[register];
+
+    state.registers[register] = combine(current, value).ok_or_else(|| {
+        format!(
+            "{} overflowed r{} combining {} with {}",
+            operation.mnemonic(),
+            register,
+            current,
+            value
+        )
+    })?;
+
+    Ok(Effect::Next)
+}
+
+pub struct NoOperation;
+
+impl Operation for NoOperation {
+    fn mnemonic(&self) -> &'static str {
+        "nop"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter::Value]
+    }
+
+    fn execute(&self, _: &[Operand], _: &mut State) -> Result<Effect, String> {
+        Ok(Effect::Next)
+    }
+}
+
+pub struct Accumulate;
+
+impl Operation for Accumulate {
+    fn mnemonic(&self) -> &'static str {
+        "acc"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter::Value]
+    }
+
+    fn execute(&self, operands: &[Operand], state: &mut State) -> Result<Effect, String> {
+        let value = operands[0].resolve(state);
+        store(self, state, ACCUMULATOR, i64::checked_add, value)
+    }
+}
+
+pub struct Jump;
+
+impl Operation for Jump {
+    fn mnemonic(&self) -> &'static str {
+        "jmp"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter::Value]
+    }
+
+    fn execute(&self, operands: &[Operand], state: &mut State) -> Result<Effect, String> {
+        Ok(Effect::Jump(operands[0].resolve(state)))
+    }
+}
+
+pub struct Move;
+
+impl Operation for Move {
+    fn mnemonic(&self) -> &'static str {
+        "mov"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter::Register, Parameter::Value]
+    }
+
+    fn execute(&self, operands: &[Operand], state: &mut State) -> Result<Effect, String> {
+        state.registers[destination(&operands[0])] = operands[1].resolve(state);
+        Ok(Effect::Next)
+    }
+}
+
+pub struct Add;
+
+impl Operation for Add {
+    fn mnemonic(&self) -> &'static str {
+        "add"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter::Register, Parameter::Value]
+    }
+
+    fn execute(&self, operands: &[Operand], state: &mut State) -> Result<Effect, String> {
+        let value = operands[1].resolve(state);
+        store(
+            self,
+            state,
+            destination(&operands[0]),
+            i64::checked_add,
+            value,
+        )
+    }
+}
+
+pub struct Multiply;
+
+impl Operation for Multiply {
+    fn mnemonic(&self) -> &'static str {
+        "mul"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter::Register, Parameter::Value]
+    }
+
+    fn execute(&self, operands: &[Operand], state: &mut State) -> Result<Effect, String> {
+        let value = operands[1].resolve(state);
+        store(
+            self,
+            state,
+            destination(&operands[0]),
+            i64::checked_mul,
+            value,
+        )
+    }
+}
+
+pub struct JumpIfZero;
+
+impl Operation for JumpIfZero {
+    fn mnemonic(&self) -> &'static str {
+        "jz"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter::Value, Parameter::Value]
+    }
+
+    fn execute(&self, operands: &[Operand], state: &mut State) -> Result<Effect, String> {
+        match operands[0].resolve(state) {
+            0 => Ok(Effect::Jump(operands[1].resolve(state))),
+            _ => Ok(Effect::Next),
+        }
+    }
+}
+
+pub struct JumpIfNotZero;
+
+impl Operation for JumpIfNotZero {
+    fn mnemonic(&self) -> &'static str {
+        "jnz"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter::Value, Parameter::Value]
+    }
+
+    fn execute(&self, operands: &[Operand], state: &mut State) -> Result<Effect, String> {
+        match operands[0].resolve(state) {
+            0 => Ok(Effect::Next),
+            _ => Ok(Effect::Jump(operands[1].resolve(state))),
+        }
+    }
+}
+
+pub struct JumpIfGreaterThanZero;
+
+impl Operation for JumpIfGreaterThanZero {
+    fn mnemonic(&self) -> &'static str {
+        "jgz"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter::Value, Parameter::Value]
+    }
+
+    fn execute(&self, operands: &[Operand], state: &mut State) -> Result<Effect, String> {
+        if operands[0].resolve(state) > 0 {
+            Ok(Effect::Jump(operands[1].resolve(state)))
+        } else {
+            Ok(Effect::Next)
+        }
+    }
+}
+
+pub struct Halt;
+
+impl Operation for Halt {
+    fn mnemonic(&self) -> &'static str {
+        "hlt"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[]
+    }
+
+    fn execute(&self, _: &[Operand], _: &mut State) -> Result<Effect, String> {
+        Ok(Effect::Halt)
+    }
+}
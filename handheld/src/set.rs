@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use crate::operations;
+use crate::{Instruction, Operand, State};
+
+/// The kind of operand an operation expects in each position.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Parameter {
+    /// Either an immediate value or a register to read from.
+    Value,
+    /// A register that will be written to.
+    Register,
+}
+
+/// What the machine should do with the program counter after an operation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Effect {
+    Next,
+    Jump(i64),
+    Halt,
+}
+
+/// The behaviour of a single instruction, which can be added to an [`InstructionSet`].
+pub trait Operation {
+    fn mnemonic(&self) -> &'static str;
+
+    fn parameters(&self) -> &'static [Parameter];
+
+    /// Executes the operation, with operands already validated against `parameters`, failing if
+    /// the result cannot be represented.
+    fn execute(&self, operands: &[Operand], state: &mut State) -> Result<Effect, String>;
+}
+
+#[derive(Default)]
+pub struct InstructionSet {
+    operations: Vec<Box<dyn Operation>>,
+    opcodes: HashMap<&'static str, usize>,
+}
+
+impl InstructionSet {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates an instruction set with the boot code operations and some register extensions.
+    pub fn standard() -> Self {
+        let mut set = Self::new();
+
+        set.register(operations::NoOperation);
+        set.register(operations::Accumulate);
+        set.register(operations::Jump);
+        set.register(operations::Move);
+        set.register(operations::Add);
+        set.register(operations::Multiply);
+        set.register(operations::JumpIfZero);
+        set.register(operations::JumpIfNotZero);
+        set.register(operations::JumpIfGreaterThanZero);
+        set.register(operations::Halt);
+
+        set
+    }
+
+    /// Adds an operation to the set, replacing any existing one with the same mnemonic.
+    pub fn register<O: Operation + 'static>(&mut self, operation: O) -> usize {
+        let mnemonic = operation.mnemonic();
+
+        if let Some(&opcode) = self.opcodes.get(mnemonic) {
+            self.operations[opcode] = Box::new(operation);
+            return opcode;
+        }
+
+        let opcode = self.operations.len();
+        self.operations.push(Box::new(operation));
+        self.opcodes.insert(mnemonic, opcode);
+
+        opcode
+    }
+
+    pub fn opcode(&self, mnemonic: &str) -> Option<usize> {
+        self.opcodes.get(mnemonic).copied()
+    }
+
+    pub fn operation(&self, opcode: usize) -> &dyn Operation {
+        self.operations[opcode].as_ref()
+    }
+
+    /// Parses a single line of assembly such as `acc +3` or `mov r1 r2`.
+    pub fn decode(&self, line: &str) -> Result<Instruction, String> {
+        let mut parts = line.split_whitespace();
+        let mnemonic = parts.next().ok_or("Missing operation")?;
+
+        let opcode = self
+            .opcode(mnemonic)
+            .ok_or(format!("Unknown operation: {}", mnemonic))?;
+
+        let operands = parts.map(str::parse).collect::<Result<Vec<Operand>, _>>()?;
+
+        let parameters = self.operation(opcode).parameters();
+
+        if operands.len() != parameters.len() {
+            return Err(format!(
+                "{} expects {} operand(s) but got {}",
+                mnemonic,
+                parameters.len(),
+                operands.len()
+            ));
+        }
+
+        for (operand, parameter) in operands.iter().zip(parameters) {
+            if let (Parameter::Register, Operand::Immediate(_)) = (parameter, operand) {
+                return Err(format!("{} expects a register, not {}", mnemonic, operand));
+            }
+        }
+
+        Ok(Instruction { opcode, operands })
+    }
+
+    /// Formats a single instruction back into its assembly form.
+    pub fn encode(&self, instruction: &Instruction) -> String {
+        let mut line = String::from(self.operation(instruction.opcode).mnemonic());
+
+        for operand in &instruction.operands {
+            line.push(' ');
+            line.push_str(&operand.to_string());
+        }
+
+        line
+    }
+
+    /// Assembles a program, ignoring blank lines and anything after a `;`.
+    pub fn assemble(&self, source: &str) -> Result<Vec<Instruction>, String> {
+        source
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i, line.split(';').next().unwrap().trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(i, line)| {
+                self.decode(line)
+                    .map_err(|e| format!("Line {}: {}", i + 1, e))
+            })
+            .collect()
+    }
+
+    pub fn disassemble(&self, program: &[Instruction]) -> String {
+        program
+            .iter()
+            .map(|instruction| self.encode(instruction))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
@@ -0,0 +1,214 @@
+//! Modular arithmetic over any primitive integer type.
+//!
+//! Functions taking a modulus expect it to be positive and the other arguments to be
+//! non-negative, so that they work the same for signed and unsigned types.
+
+use num_traits::{PrimInt, Signed};
+
+pub fn gcd<T: PrimInt>(a: T, b: T) -> T {
+    if b.is_zero() {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Gets the lowest common multiple, or `None` if it overflows.
+pub fn lcm<T: PrimInt>(a: T, b: T) -> Option<T> {
+    if a.is_zero() || b.is_zero() {
+        return Some(T::zero());
+    }
+
+    (a / gcd(a, b)).checked_mul(&b)
+}
+
+/// Finds `(g, x, y)` such that `a * x + b * y == g`, where `g` is the greatest common divisor.
+pub fn extended_gcd<T: PrimInt + Signed>(a: T, b: T) -> (T, T, T) {
+    if b.is_zero() {
+        return (a, T::one(), T::zero());
+    }
+
+    let (g, x, y) = extended_gcd(b, a % b);
+    (g, y, x - (a / b) * y)
+}
+
+/// Adds two residues without overflowing, even when `a + b` would.
+pub fn add_mod<T: PrimInt>(a: T, b: T, m: T) -> T {
+    let (a, b) = (a % m, b % m);
+
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+pub fn sub_mod<T: PrimInt>(a: T, b: T, m: T) -> T {
+    let (a, b) = (a % m, b % m);
+
+    if a >= b {
+        a - b
+    } else {
+        m - (b - a)
+    }
+}
+
+/// Multiplies two residues, falling back to doubling and adding if `a * b` would overflow.
+pub fn mul_mod<T: PrimInt>(a: T, b: T, m: T) -> T {
+    let (mut a, mut b) = (a % m, b % m);
+
+    if let Some(product) = a.checked_mul(&b) {
+        return product % m;
+    }
+
+    let mut result = T::zero();
+
+    while !b.is_zero() {
+        if (b & T::one()).is_one() {
+            result = add_mod(result, a, m);
+        }
+
+        a = add_mod(a, a, m);
+        b = b >> 1;
+    }
+
+    result
+}
+
+/// Computes `base ^ exponent % m` by repeated squaring.
+pub fn mod_pow<T: PrimInt>(base: T, exponent: T, m: T) -> T {
+    let (mut base, mut exponent) = (base % m, exponent);
+    let mut result = T::one() % m;
+
+    while !exponent.is_zero() {
+        if (exponent & T::one()).is_one() {
+            result = mul_mod(result, base, m);
+        }
+
+        base = mul_mod(base, base, m);
+        exponent = exponent >> 1;
+    }
+
+    result
+}
+
+/// Finds `x` such that `a * x % m == 1`, or `None` if `a` and `m` are not coprime.
+pub fn mod_inverse<T: PrimInt>(a: T, m: T) -> Option<T> {
+    // The extended Euclidean algorithm, keeping coefficients reduced so they stay non-negative
+    let (mut r0, mut r1) = (m, a % m);
+    let (mut t0, mut t1) = (T::zero(), T::one() % m);
+
+    while !r1.is_zero() {
+        let q = r0 / r1;
+
+        let r = r0 - q * r1;
+        r0 = std::mem::replace(&mut r1, r);
+
+        let t = sub_mod(t0, mul_mod(q, t1, m), m);
+        t0 = std::mem::replace(&mut t1, t);
+    }
+
+    if r0.is_one() {
+        Some(t0)
+    } else {
+        None
+    }
+}
+
+/// Solves a system of congruences `x % modulus == residue`, returning the smallest solution and
+/// the modulus it repeats with.
+///
+/// The moduli do not need to be coprime, but the system fails if the congruences contradict each
+/// other or if the combined modulus overflows.
+pub fn crt<T: PrimInt + std::fmt::Display>(congruences: &[(T, T)]) -> Result<(T, T), String> {
+    let mut solution = (T::zero(), T::one());
+
+    for &(residue, modulus) in congruences {
+        let (a, m) = solution;
+
+        let g = gcd(m, modulus);
+        let difference = sub_mod(residue, a, modulus);
+
+        if !(difference % g).is_zero() {
+            return Err(format!(
+                "x = {} (mod {}) contradicts x = {} (mod {})",
+                residue, modulus, a, m
+            ));
+        }
+
+        let combined = lcm(m, modulus)
+            .ok_or_else(|| format!("Combining modulus {} with {} overflows", modulus, m))?;
+
+        // Step from `a` by multiples of `m` until the new congruence also holds
+        let reduced = modulus / g;
+        let inverse = mod_inverse(m / g, reduced).unwrap();
+        let k = mul_mod(difference / g, inverse, reduced);
+
+        solution = (a + m * k, combined);
+    }
+
+    Ok(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_gcd_finds_bezout_coefficients() {
+        for &(a, b) in &[(240, 46), (46, 240), (17, 5), (12, 0), (0, 7), (-35, 15)] {
+            let (g, x, y) = extended_gcd::<i64>(a, b);
+
+            assert_eq!(g.abs(), gcd(a.abs(), b.abs()));
+            assert_eq!(a * x + b * y, g);
+        }
+    }
+
+    #[test]
+    fn inverses_exist_only_for_coprime_values() {
+        assert_eq!(mod_inverse(3u32, 11), Some(4));
+        assert_eq!(mod_inverse(10i64, 17), Some(12));
+        assert_eq!(mod_inverse(6u8, 9), None);
+        assert_eq!(mod_inverse(5u64, 1), Some(0));
+
+        for a in 1..97u16 {
+            assert_eq!(mod_inverse(a, 97).map(|x| a * x % 97), Some(1));
+        }
+    }
+
+    #[test]
+    fn arithmetic_near_the_limits_does_not_overflow() {
+        let m = u64::MAX - 58; // The largest 64 bit prime
+
+        assert_eq!(add_mod(m - 1, m - 1, m), m - 2);
+        assert_eq!(sub_mod(0, 1, m), m - 1);
+        assert_eq!(mul_mod(m - 1, m - 1, m), 1);
+        assert_eq!(mod_pow(2, m - 1, m), 1);
+
+        let inverse = mod_inverse(123_456_789, m).unwrap();
+        assert_eq!(mul_mod(inverse, 123_456_789, m), 1);
+    }
+
+    #[test]
+    fn powers_match_repeated_multiplication() {
+        assert_eq!(mod_pow(4u32, 13, 497), 445);
+        assert_eq!(mod_pow(7i32, 0, 13), 1);
+        assert_eq!(mod_pow(7u8, 0, 1), 0);
+        assert_eq!(mod_pow(2u128, 127, u128::MAX), 1 << 127);
+    }
+
+    #[test]
+    fn congruences_can_be_combined() {
+        assert_eq!(crt::<u64>(&[]), Ok((0, 1)));
+        assert_eq!(crt(&[(2u32, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+
+        // Moduli sharing factors work as long as the congruences agree
+        assert_eq!(crt(&[(2i64, 6), (8, 10)]), Ok((8, 30)));
+        assert_eq!(
+            crt(&[(0u8, 4), (2, 6), (1, 3)]),
+            Err(String::from("x = 1 (mod 3) contradicts x = 8 (mod 12)"))
+        );
+
+        assert!(crt(&[(0u8, 16), (0, 17)]).is_err());
+    }
+}